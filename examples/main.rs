@@ -1,10 +1,16 @@
 use rst_lib::scanner::detector::winnow::{aws::api_key, private_key::pem};
+use rst_lib::scanner::detector::Backend;
+use rst_lib::scanner::redact::Redaction;
+use rst_lib::scanner::source::filesystem::client::FsClient;
 use rst_lib::scanner::source::git::client::{FileSystemPath, GitClient};
+use std::path::Path;
 
 fn main() {
     aws_key();
     pem_key();
     walker_texas_ranger();
+    scan_history();
+    scan_directory();
 }
 
 fn aws_key() {
@@ -16,7 +22,8 @@ fn aws_key() {
         return;
     }
 
-    println!("[AWS] Result: {}", result.unwrap());
+    // the key is redacted, as is any secret written out
+    println!("[AWS] Result: {}", Redaction::default().apply(&result.unwrap()));
 }
 
 fn pem_key() {
@@ -47,12 +54,8 @@ vhj3eVN6voMtw7o=
     let mut pkey = result.unwrap();
     let _ = pkey.validate();
 
-    let pem_str = pkey.to_string();
-    if pem_str.is_err() {
-        println!("[PEM] Error: {:?}", pem_str.err().unwrap());
-        return;
-    }
-    println!("[PEM] Private Key:\n {}", pem_str.unwrap().trim());
+    // displayed with the key material masked, see `Pem::to_pem` for the key itself
+    println!("[PEM] Private Key:\n {}", pkey);
 
     let pub_key = pkey.get_public_key();
     if pub_key.is_err() {
@@ -88,4 +91,62 @@ fn walker_texas_ranger() {
         Ok(_) => {}
         Err(_) => {}
     }
-}
\ No newline at end of file
+}
+
+fn scan_history() {
+    let git_url = FileSystemPath { path: "./" };
+
+    let client = match GitClient::from(&git_url) {
+        Ok(client) => client,
+        Err(e) => {
+            println!("[GIT] Error: {:?}", e);
+            return;
+        }
+    };
+
+    let detectors = Backend::default().detectors();
+    let result = client.scan(&detectors, |finding| {
+        println!(
+            "[GIT] {} in {} {}:{}:{} by {} <{}> at {}",
+            finding.finding.kind().name(),
+            finding.commit,
+            finding.path,
+            finding.line,
+            finding.column,
+            finding.author,
+            finding.email,
+            finding.timestamp
+        );
+        true
+    });
+
+    if let Err(e) = result {
+        println!("[GIT] Error: {:?}", e);
+    }
+}
+
+fn scan_directory() {
+    let client = match FsClient::from(Path::new("./")) {
+        Ok(client) => client,
+        Err(e) => {
+            println!("[FS] Error: {:?}", e);
+            return;
+        }
+    };
+
+    let detectors = Backend::default().detectors();
+    let result = client.scan(&detectors, |finding| {
+        println!(
+            "[FS] {} in {}:{}:{}",
+            finding.finding.kind().name(),
+            finding.path.display(),
+            finding.line,
+            finding.column
+        );
+        true
+    });
+
+    if let Err(e) = result {
+        println!("[FS] Error: {:?}", e);
+    }
+}
@@ -0,0 +1,210 @@
+use clap::{ArgAction, Args, Parser, Subcommand, ValueEnum};
+use rst_lib::scanner::detector::Backend;
+use rst_lib::scanner::redact::{Redaction, DEFAULT_KEEP_FIRST, DEFAULT_KEEP_LAST};
+use rst_lib::scanner::source::archive::client::{MAX_DEPTH, MAX_RATIO, MAX_TOTAL_BYTES};
+use rst_lib::scanner::source::filesystem::client::{IGNORE_FILE, MAX_FILE_SIZE};
+use rst_lib::scanner::source::git::revisions;
+use std::path::PathBuf;
+
+// Exit codes, other than the one used when there are findings
+pub const EXIT_CLEAN: u8 = 0;
+pub const EXIT_ERROR: u8 = 2;
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "rst",
+    version,
+    about = "Scans git history, directories, archives, container images and stdin for secrets"
+)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Command,
+
+    /// Parser backend to detect findings with
+    #[arg(long, global = true, default_value = "winnow", value_parser = backend)]
+    pub backend: Backend,
+
+    /// Format findings are written to stdout in
+    #[arg(long, global = true, value_enum, default_value_t = Format::Text)]
+    pub format: Format,
+
+    /// How secrets are redacted in the findings written
+    #[arg(long, global = true, value_enum, default_value_t = Redact::Partial)]
+    pub redact: Redact,
+
+    /// Characters kept at the start of a secret redacted with --redact partial
+    #[arg(long, global = true, default_value_t = DEFAULT_KEEP_FIRST)]
+    pub keep_first: usize,
+
+    /// Characters kept at the end of a secret redacted with --redact partial
+    #[arg(long, global = true, default_value_t = DEFAULT_KEEP_LAST)]
+    pub keep_last: usize,
+
+    /// Write the secrets found, unredacted
+    #[arg(long, global = true, conflicts_with = "redact")]
+    pub show_secrets: bool,
+
+    /// Exit code when there are findings
+    #[arg(long, global = true, default_value_t = 1)]
+    pub exit_code: u8,
+
+    /// Log more to stderr (-v for a summary, -vv for every file scanned)
+    #[arg(short, long, global = true, action = ArgAction::Count)]
+    pub verbose: u8,
+
+    /// Log nothing but fatal errors to stderr
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    pub quiet: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum Redact {
+    Mask,
+    Partial,
+    Hash,
+}
+
+impl Cli {
+    pub fn redaction(&self) -> Redaction {
+        if self.show_secrets {
+            return Redaction::Raw;
+        }
+
+        match self.redact {
+            Redact::Mask => Redaction::Mask,
+            Redact::Partial => Redaction::Partial {
+                first: self.keep_first,
+                last: self.keep_last,
+            },
+            Redact::Hash => Redaction::Hash,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum Format {
+    Text,
+    Sarif,
+    Json,
+    #[value(name = "jsonl")]
+    JsonLines,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Scan a source for findings
+    #[command(subcommand)]
+    Scan(ScanCommand),
+
+    /// Detect and validate the findings in a file, e.g. that a private key parses
+    Validate { file: PathBuf },
+
+    /// List the detectors, and the parser backends they are built with
+    ListDetectors,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ScanCommand {
+    /// Scan the lines added by each commit of a repository, by URL or path
+    Git(GitArgs),
+
+    /// Scan the files of a directory
+    Fs(FsArgs),
+
+    /// Scan the files within an archive, and any archives nested in it
+    Archive(ArchiveArgs),
+
+    /// Scan a container image saved by `docker save`, or an OCI layout
+    Image { path: PathBuf },
+
+    /// Scan standard input
+    Stdin,
+}
+
+#[derive(Args, Debug)]
+pub struct GitArgs {
+    /// URL (e.g. https://github.com/owner/repo.git, git@host:owner/repo.git or owner/repo) or path
+    pub target: String,
+
+    /// Walk every branch, rather than HEAD
+    #[arg(long)]
+    pub all_branches: bool,
+
+    /// Walk every tag, rather than HEAD
+    #[arg(long)]
+    pub all_tags: bool,
+
+    /// Walk a ref glob (e.g. refs/heads/release/*) or range (e.g. main..feature)
+    #[arg(long = "ref", value_name = "REF")]
+    pub refs: Vec<String>,
+
+    /// Only commits made at or after, as YYYY-MM-DD[THH:MM:SS] or seconds since the epoch
+    #[arg(long, value_parser = date)]
+    pub since: Option<i64>,
+
+    /// Only commits made at or before
+    #[arg(long, value_parser = date)]
+    pub until: Option<i64>,
+
+    /// Keep mirrors of remote repositories here, and fetch them on the next run
+    #[arg(long)]
+    pub cache_dir: Option<PathBuf>,
+
+    /// Environment variable to read an access token for HTTPS remotes from
+    #[arg(long, value_name = "VAR")]
+    pub token_env: Option<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct FsArgs {
+    pub dir: PathBuf,
+
+    /// Scan files matched by .gitignore files too
+    #[arg(long)]
+    pub no_gitignore: bool,
+
+    /// Name of the custom ignore files, in .gitignore syntax
+    #[arg(long, default_value = IGNORE_FILE)]
+    pub ignore_file: String,
+
+    /// Skip files larger than this, in bytes
+    #[arg(long, default_value_t = MAX_FILE_SIZE)]
+    pub max_file_size: u64,
+
+    /// Follow symlinks
+    #[arg(long)]
+    pub follow_symlinks: bool,
+
+    /// Threads to walk with, 0 to pick from the number of CPUs
+    #[arg(long, default_value_t = 0)]
+    pub threads: usize,
+}
+
+#[derive(Args, Debug)]
+pub struct ArchiveArgs {
+    pub path: PathBuf,
+
+    /// Open archives nested up to this deep
+    #[arg(long, default_value_t = MAX_DEPTH)]
+    pub max_depth: usize,
+
+    /// Stop once this many bytes have been decompressed
+    #[arg(long, default_value_t = MAX_TOTAL_BYTES)]
+    pub max_total_bytes: u64,
+
+    /// Stop once this many times the size of the archive has been decompressed
+    #[arg(long, default_value_t = MAX_RATIO)]
+    pub max_ratio: u64,
+}
+
+fn backend(name: &str) -> Result<Backend, String> {
+    Backend::from_name(name).ok_or_else(|| {
+        let names: Vec<&str> = Backend::ALL.iter().map(|b| b.name()).collect();
+        format!("expected one of: {}", names.join(", "))
+    })
+}
+
+fn date(date: &str) -> Result<i64, String> {
+    revisions::parse_date(date)
+        .map_err(|_| String::from("expected YYYY-MM-DD[THH:MM:SS] or seconds since the epoch"))
+}
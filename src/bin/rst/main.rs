@@ -0,0 +1,325 @@
+mod cli;
+
+#[cfg(test)]
+mod test;
+
+use clap::Parser;
+use cli::{
+    ArchiveArgs, Cli, Command, Format, FsArgs, GitArgs, ScanCommand, EXIT_CLEAN, EXIT_ERROR,
+};
+use rst_lib::scanner::detector::{Backend, DetectorSet};
+use rst_lib::scanner::finding::private_key::pem::PemStatus;
+use rst_lib::scanner::finding::{Finding, FindingKind};
+use rst_lib::scanner::report::json::{JsonLinesReporter, JsonReporter};
+use rst_lib::scanner::report::sarif::{self, SarifReporter};
+use rst_lib::scanner::report::text::{self, TextReporter};
+use rst_lib::scanner::report::Reporter;
+use rst_lib::scanner::source::archive::client::{ArchiveClient, ArchiveOptions};
+use rst_lib::scanner::source::filesystem::client::{FsClient, FsOptions};
+use rst_lib::scanner::source::git::auth::GitAuth;
+use rst_lib::scanner::source::git::client::{
+    FileSystemPath, GitClient, GitOptions, GitPath, GitUrl,
+};
+use rst_lib::scanner::source::git::revisions::{GitRef, Revisions};
+use rst_lib::scanner::source::image::client::ImageClient;
+use rst_lib::scanner::source::stdin::StdinSource;
+use rst_lib::scanner::source::{Provenance, Source, SourceKind};
+use std::env;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let mut stdout = io::stdout().lock();
+    let mut stderr = io::stderr();
+
+    ExitCode::from(run(&cli, &mut stdout, &mut stderr))
+}
+
+// Messages to stderr, as verbose as asked for
+struct Log<'a> {
+    writer: &'a mut dyn Write,
+    verbosity: i8, // -1 if quiet
+}
+
+impl Log<'_> {
+    fn error(&mut self, message: &str) {
+        let _ = writeln!(self.writer, "error: {}", message);
+    }
+
+    fn warn(&mut self, message: &str) {
+        if self.verbosity >= 0 {
+            let _ = writeln!(self.writer, "warning: {}", message);
+        }
+    }
+
+    fn info(&mut self, message: &str) {
+        if self.verbosity >= 1 {
+            let _ = writeln!(self.writer, "{}", message);
+        }
+    }
+
+    fn debug(&mut self, message: &str) {
+        if self.verbosity >= 2 {
+            let _ = writeln!(self.writer, "{}", message);
+        }
+    }
+}
+
+// Runs the command, returning the exit code
+fn run(cli: &Cli, out: &mut dyn Write, err: &mut dyn Write) -> u8 {
+    let mut log = Log {
+        writer: err,
+        verbosity: match cli.quiet {
+            true => -1,
+            false => cli.verbose as i8,
+        },
+    };
+
+    let result = match &cli.command {
+        Command::Scan(command) => scan_command(cli, command, out, &mut log),
+        Command::Validate { file } => validate(cli.backend, file, out, &mut log),
+        Command::ListDetectors => list_detectors(out).map(|_| 0),
+    };
+
+    match result {
+        Ok(0) => EXIT_CLEAN,
+        Ok(_) => cli.exit_code,
+        Err(message) => {
+            log.error(&message);
+            EXIT_ERROR
+        }
+    }
+}
+
+fn reporter<'a>(cli: &Cli, out: &'a mut dyn Write) -> Box<dyn Reporter + 'a> {
+    let redaction = cli.redaction();
+    match cli.format {
+        Format::Text => Box::new(TextReporter::with_redaction(out, redaction)),
+        Format::Sarif => Box::new(SarifReporter::with_redaction(out, redaction)),
+        Format::Json => Box::new(JsonReporter::with_redaction(out, redaction)),
+        Format::JsonLines => Box::new(JsonLinesReporter::with_redaction(out, redaction)),
+    }
+}
+
+// Scans the source, returning how many findings were reported
+fn scan_command(
+    cli: &Cli,
+    command: &ScanCommand,
+    out: &mut dyn Write,
+    log: &mut Log,
+) -> Result<usize, String> {
+    let detectors = cli.backend.detectors();
+    let mut reporter = reporter(cli, out);
+    let reporter = reporter.as_mut();
+
+    match command {
+        ScanCommand::Git(args) => scan_git(args, &detectors, reporter, log),
+        ScanCommand::Fs(args) => {
+            let mut source = FsClient::with_options(&args.dir, fs_options(args))
+                .map_err(|e| format!("{}: {:?}", args.dir.display(), e))?;
+            scan(&mut source, &detectors, reporter, log)
+        }
+        ScanCommand::Archive(args) => {
+            let mut source = ArchiveClient::with_options(&args.path, archive_options(args))
+                .map_err(|e| format!("{}: {:?}", args.path.display(), e))?;
+            scan(&mut source, &detectors, reporter, log)
+        }
+        ScanCommand::Image { path } => {
+            let mut source =
+                ImageClient::from(path).map_err(|e| format!("{}: {:?}", path.display(), e))?;
+            scan(&mut source, &detectors, reporter, log)
+        }
+        ScanCommand::Stdin => scan(&mut StdinSource::new(), &detectors, reporter, log),
+    }
+}
+
+// Scans the source, returning how many findings were reported. Every finding
+// is reported even if the source fails part way, but a scan with any errors
+// fails, as some of the source may not have been scanned.
+fn scan<S: Source>(
+    source: &mut S,
+    detectors: &DetectorSet,
+    reporter: &mut dyn Reporter,
+    log: &mut Log,
+) -> Result<usize, String> {
+    let kind = source.kind();
+    let (mut chunks, mut findings) = (0, 0);
+    let mut errors: Vec<String> = Vec::new();
+
+    for chunk in source.chunks() {
+        let chunk = match chunk {
+            Ok(chunk) => chunk,
+            Err(e) => {
+                let error = format!("{} source: {:?}", kind.name(), e);
+                log.warn(&error);
+                errors.push(error);
+                continue;
+            }
+        };
+        log.debug(&format!("scanning {}", describe(&chunk.provenance)));
+        chunks += 1;
+
+        for finding in chunk.scan(detectors) {
+            reporter.report(&finding).map_err(|e| e.to_string())?;
+            findings += 1;
+        }
+    }
+    reporter.finish().map_err(|e| e.to_string())?;
+
+    log.info(&format!(
+        "{} findings in {} chunks scanned, with {} errors",
+        findings,
+        chunks,
+        errors.len()
+    ));
+    match errors.len() {
+        0 => Ok(findings),
+        1 => Err(errors.remove(0)),
+        n => Err(format!("{} (and {} more errors)", errors.remove(0), n - 1)),
+    }
+}
+
+fn describe(provenance: &Provenance) -> String {
+    let path = provenance.path.as_deref().unwrap_or("<stdin>");
+    match (&provenance.commit, &provenance.layer) {
+        (Some(commit), _) => format!("{} at {}", path, commit.id),
+        (_, Some(layer)) => format!("{} in {}", path, layer),
+        _ => String::from(path),
+    }
+}
+
+fn scan_git(
+    args: &GitArgs,
+    detectors: &DetectorSet,
+    reporter: &mut dyn Reporter,
+    log: &mut Log,
+) -> Result<usize, String> {
+    let local = FileSystemPath { path: &args.target };
+    let remote = GitUrl {
+        url: args.target.clone(),
+    };
+    let path: &dyn GitPath = match Path::new(&args.target).exists() {
+        true => &local,
+        false => &remote,
+    };
+
+    let mut refs: Vec<GitRef> = Vec::new();
+    if args.all_branches {
+        refs.push(GitRef::AllBranches);
+    }
+    if args.all_tags {
+        refs.push(GitRef::AllTags);
+    }
+    for git_ref in &args.refs {
+        refs.push(match git_ref.contains("..") {
+            true => GitRef::Range(git_ref.clone()),
+            false => GitRef::Glob(git_ref.clone()),
+        });
+    }
+
+    let auth = match &args.token_env {
+        Some(var) => GitAuth::Token {
+            token: env::var(var).map_err(|_| format!("{} is not set", var))?,
+        },
+        None => GitAuth::None,
+    };
+    let options = GitOptions {
+        auth,
+        cache_dir: args.cache_dir.clone(),
+        revisions: Revisions {
+            refs,
+            since: args.since,
+            until: args.until,
+            ..Default::default()
+        },
+    };
+
+    log.debug(&format!("opening {}", args.target));
+    let mut source =
+        GitClient::with_options(path, options).map_err(|e| format!("{}: {:?}", args.target, e))?;
+    if let Ok((kind, url)) = path.get_path() {
+        reporter.repository(&sarif::repository_uri(kind, &url));
+    }
+    scan(&mut source, detectors, reporter, log)
+}
+
+fn fs_options(args: &FsArgs) -> FsOptions {
+    FsOptions {
+        git_ignore: !args.no_gitignore,
+        ignore_file: Some(args.ignore_file.clone()),
+        max_file_size: Some(args.max_file_size),
+        follow_symlinks: args.follow_symlinks,
+        threads: args.threads,
+    }
+}
+
+fn archive_options(args: &ArchiveArgs) -> ArchiveOptions {
+    ArchiveOptions {
+        max_depth: args.max_depth,
+        max_total_bytes: args.max_total_bytes,
+        max_ratio: args.max_ratio,
+    }
+}
+
+// Validates each finding in the file, returning how many are valid. A private
+// key or certificate is valid if it parses; other PEMs can't be checked, and an
+// AWS API key can only be checked for its format.
+fn validate(
+    backend: Backend,
+    file: &Path,
+    out: &mut dyn Write,
+    log: &mut Log,
+) -> Result<usize, String> {
+    let content = fs::read(file).map_err(|e| format!("{}: {}", file.display(), e))?;
+    let detectors = backend.detectors();
+
+    let mut provenance = Provenance::new(SourceKind::Filesystem);
+    provenance.path = Some(file.to_string_lossy().into_owned());
+
+    let mut valid = 0;
+    for detection in detectors.detect(&content) {
+        let kind = detection.finding.kind();
+        let (status, is_valid) = match detection.finding {
+            Finding::AwsApiKey(_) => (String::from("format valid, not verified"), true),
+            Finding::Pem(pem) => match pem.check().0 {
+                PemStatus::Valid => (String::from("valid"), true),
+                PemStatus::Unknown => (String::from("unknown, not verified"), false),
+                status => (format!("invalid ({:?})", status), false),
+            },
+        };
+        if is_valid {
+            valid += 1;
+        }
+
+        writeln!(
+            out,
+            "{} at {}: {}",
+            kind.name(),
+            text::location(&provenance, detection.line, detection.column),
+            status
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    log.info(&format!("{} valid findings", valid));
+    Ok(valid)
+}
+
+fn list_detectors(out: &mut dyn Write) -> Result<(), String> {
+    let backends: Vec<&str> = Backend::ALL.iter().map(|b| b.name()).collect();
+    for kind in FindingKind::ALL {
+        writeln!(
+            out,
+            "{:<16} {:<16} {}",
+            kind.id(),
+            kind.name(),
+            backends.join(", ")
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    Ok(())
+}
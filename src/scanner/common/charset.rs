@@ -158,3 +158,20 @@ pub const BASE64_WITH_PADDING_WS: (
     (RangeInclusive<char>, char),
 ) = (BASE64_WITH_PADDING, ASCII_WHITESPACE);
 pub const URL_CHARS: RangeInclusive<char> = '!'..='~';
+
+// Predicates over the above character sets, for parsers that match on a single `char`
+pub fn is_base64(c: char) -> bool {
+    ASCII_ALPHA_NUM.0.contains(&c)
+        || ASCII_ALPHA_NUM.1.contains(&c)
+        || ASCII_ALPHA_NUM.2.contains(&c)
+        || c == BASE64_SYMBOL.0
+        || c == BASE64_SYMBOL.1
+}
+
+pub fn is_ascii_whitespace(c: char) -> bool {
+    ASCII_WHITESPACE.0.contains(&c) || c == ASCII_WHITESPACE.1
+}
+
+pub fn is_base64_ws(c: char) -> bool {
+    is_base64(c) || is_ascii_whitespace(c)
+}
@@ -0,0 +1,75 @@
+use crate::scanner::detector::traits::{Detection, Detector};
+use crate::scanner::detector::{chumsky, pest, vectorscan, winnow};
+use crate::scanner::finding::private_key::pem::Pem;
+use crate::scanner::finding::Finding;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Backend {
+    #[default]
+    Winnow,
+    Chumsky,
+    Pest,
+    Vectorscan,
+}
+
+impl Backend {
+    pub const ALL: [Backend; 4] = [
+        Backend::Winnow,
+        Backend::Chumsky,
+        Backend::Pest,
+        Backend::Vectorscan,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Backend::Winnow => "winnow",
+            Backend::Chumsky => "chumsky",
+            Backend::Pest => "pest",
+            Backend::Vectorscan => "vectorscan",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Backend> {
+        Backend::ALL.into_iter().find(|b| b.name() == name)
+    }
+
+    pub fn aws_api_key(&self) -> Box<dyn Detector<Finding = String>> {
+        match self {
+            Backend::Winnow => Box::new(winnow::aws::api_key::ApiKeyDetector),
+            Backend::Chumsky => Box::new(chumsky::aws::api_key::ApiKeyDetector),
+            Backend::Pest => Box::new(pest::aws::api_key::ApiKeyDetector),
+            Backend::Vectorscan => Box::new(vectorscan::aws::api_key::ApiKeyDetector),
+        }
+    }
+
+    pub fn pem(&self) -> Box<dyn Detector<Finding = Pem>> {
+        match self {
+            Backend::Winnow => Box::new(winnow::private_key::pem::PemDetector),
+            Backend::Chumsky => Box::new(chumsky::private_key::pem::PemDetector),
+            Backend::Pest => Box::new(pest::private_key::pem::PemDetector),
+            Backend::Vectorscan => Box::new(vectorscan::private_key::pem::PemDetector),
+        }
+    }
+
+    pub fn detectors(&self) -> DetectorSet {
+        DetectorSet {
+            aws_api_key: self.aws_api_key(),
+            pem: self.pem(),
+        }
+    }
+}
+
+// Every detector of a single backend, producing findings of any type
+pub struct DetectorSet {
+    pub aws_api_key: Box<dyn Detector<Finding = String>>,
+    pub pem: Box<dyn Detector<Finding = Pem>>,
+}
+
+impl DetectorSet {
+    pub fn detect<'a>(&'a self, input: &'a [u8]) -> impl Iterator<Item = Detection<Finding>> + 'a {
+        self.aws_api_key
+            .detect(input)
+            .map(|d| d.map(Finding::AwsApiKey))
+            .chain(self.pem.detect(input).map(|d| d.map(Finding::Pem)))
+    }
+}
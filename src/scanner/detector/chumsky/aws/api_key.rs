@@ -1 +1,60 @@
+use crate::scanner::common::charset;
+use crate::scanner::detector::error::{DetectorError, DetectorErrorKind};
+use crate::scanner::detector::scan;
+use crate::scanner::detector::traits::{Detection, Detector};
+use crate::scanner::detector::Backend;
+use crate::scanner::finding::FindingKind;
+use chumsky::prelude::*;
+use std::ops::Range;
 
+fn aws_prefix() -> impl Parser<char, &'static str, Error = Simple<char>> {
+    choice((just("AKIA"), just("ABIA"), just("ACCA"), just("ASIA")))
+}
+
+fn aws_base64() -> impl Parser<char, String, Error = Simple<char>> {
+    filter(|c: &char| charset::is_base64(*c))
+        .repeated()
+        .exactly(16)
+        .collect::<String>()
+}
+
+pub fn parse(input: &str) -> Result<String, DetectorError> {
+    if input.len() != 20 {
+        return Err(DetectorError {
+            kind: DetectorErrorKind::InvalidSize,
+        });
+    }
+
+    match aws_prefix()
+        .then(aws_base64())
+        .then_ignore(end())
+        .parse(input)
+    {
+        Ok((prefix, data)) => Ok(prefix.to_owned() + data.as_str()),
+        Err(_) => Err(DetectorError {
+            kind: DetectorErrorKind::NoMatch,
+        }),
+    }
+}
+
+fn api_key_at(input: &str) -> Option<(String, Range<usize>)> {
+    scan::aws_api_key_at(input, |candidate| parse(candidate).ok())
+}
+
+pub struct ApiKeyDetector;
+
+impl Detector for ApiKeyDetector {
+    type Finding = String;
+
+    fn kind(&self) -> FindingKind {
+        FindingKind::AwsApiKey
+    }
+
+    fn backend(&self) -> Backend {
+        Backend::Chumsky
+    }
+
+    fn detect<'a>(&'a self, input: &'a [u8]) -> Box<dyn Iterator<Item = Detection<String>> + 'a> {
+        Box::new(scan::scan(input, &scan::AWS_API_KEY_TRIGGERS, api_key_at))
+    }
+}
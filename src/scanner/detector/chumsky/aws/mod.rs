@@ -1,4 +1 @@
 pub mod api_key;
-
-#[cfg(test)]
-pub mod test;
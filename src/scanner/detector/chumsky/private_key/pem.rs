@@ -1 +1,288 @@
+use crate::scanner::common::charset;
+use crate::scanner::detector::error::{DetectorError, DetectorErrorKind};
+use crate::scanner::detector::scan;
+use crate::scanner::detector::traits::{Detection, Detector};
+use crate::scanner::detector::Backend;
+use crate::scanner::finding::private_key::pem::{
+    Pem, PemData, PemErr, BOUNDARY_BEGIN_FOOTER, BOUNDARY_BEGIN_HEADER, BOUNDARY_END,
+};
+use crate::scanner::finding::FindingKind;
+use chumsky::prelude::*;
+use chumsky::Stream;
+use std::ops::Range;
+use zeroize::{Zeroize, Zeroizing};
 
+// Detector for PEM Format as specified in RFC-7468:
+// https://www.rfc-editor.org/rfc/rfc7468
+//
+// Accepts the same input as the winnow detector. Near-miss blocks are reported
+// through chumsky's error recovery: rather than failing on the first problem,
+// the data, padding and label checks emit an error and carry on, so that
+// `diagnose` can list every reason a block was rejected.
+
+const LABEL_HEADER: &str = "header";
+const LABEL_FOOTER: &str = "footer";
+const LABEL_LABEL: &str = "label";
+const LABEL_DATA: &str = "data";
+const LABEL_PADDING: &str = "padding";
+
+// header label, data & footer label
+type PemParts = (String, Zeroizing<String>, String);
+
+type CharStream<'a> =
+    Stream<'a, char, Range<usize>, Box<dyn Iterator<Item = (char, Range<usize>)> + 'a>>;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PemRejectReason {
+    InvalidHeader,
+    InvalidFooter,
+    InvalidLabel,
+    InvalidData,
+    InvalidPadding,
+    LabelMismatch, // header & footer dont match
+    Unknown,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PemRejection {
+    pub reason: PemRejectReason,
+    pub span: Range<usize>, // in bytes
+    pub message: String,
+}
+
+impl PemRejection {
+    fn from(error: Simple<char>) -> PemRejection {
+        let reason = match error.label() {
+            Some(LABEL_HEADER) => PemRejectReason::InvalidHeader,
+            Some(LABEL_FOOTER) => PemRejectReason::InvalidFooter,
+            Some(LABEL_LABEL) => PemRejectReason::InvalidLabel,
+            Some(LABEL_DATA) => PemRejectReason::InvalidData,
+            Some(LABEL_PADDING) => PemRejectReason::InvalidPadding,
+            _ => match error.reason() {
+                chumsky::error::SimpleReason::Custom(_) => PemRejectReason::LabelMismatch,
+                _ => PemRejectReason::Unknown,
+            },
+        };
+
+        PemRejection {
+            reason,
+            span: error.span(),
+            message: error.to_string(),
+        }
+    }
+}
+
+fn whitespace() -> impl Parser<char, (), Error = Simple<char>> + Clone {
+    filter(|c: &char| c.is_whitespace()).repeated().ignored()
+}
+
+fn is_label_char(c: char) -> bool {
+    matches!(c, ' '..=',' | '.'..='`' | '{'..='~')
+}
+
+pub fn pem_label() -> impl Parser<char, String, Error = Simple<char>> + Clone {
+    filter(|c: &char| is_label_char(*c))
+        .repeated()
+        .at_least(1)
+        .collect::<String>()
+        .validate(|label: String, span, emit| {
+            if label.starts_with(' ') || label.ends_with(' ') || label.contains("  ") {
+                emit(Simple::custom(
+                    span,
+                    "label has a leading, trailing or repeated space",
+                ))
+            }
+            label
+        })
+        .labelled(LABEL_LABEL)
+}
+
+fn pem_boundary(begin: &'static str) -> impl Parser<char, String, Error = Simple<char>> + Clone {
+    just(begin)
+        .ignore_then(pem_label())
+        .then_ignore(just(BOUNDARY_END))
+}
+
+pub fn pem_header() -> impl Parser<char, String, Error = Simple<char>> + Clone {
+    pem_boundary(BOUNDARY_BEGIN_HEADER).labelled(LABEL_HEADER)
+}
+
+pub fn pem_footer() -> impl Parser<char, String, Error = Simple<char>> + Clone {
+    pem_boundary(BOUNDARY_BEGIN_FOOTER).labelled(LABEL_FOOTER)
+}
+
+pub fn pem_data() -> impl Parser<char, Zeroizing<String>, Error = Simple<char>> + Clone {
+    // take everything up to the padding or footer, so that invalid characters
+    // can be reported rather than ending the data early
+    let body = filter(|c: &char| *c != '-' && *c != charset::BASE64_SYMBOL_PADDING)
+        .map_with_span(|c, span| (c, span))
+        .repeated()
+        .at_least(1)
+        .labelled(LABEL_DATA);
+
+    let padding = just(charset::BASE64_SYMBOL_PADDING)
+        .repeated()
+        .collect::<String>();
+
+    body.then(padding).validate(
+        |(mut body, padding): (Vec<(char, Range<usize>)>, String), span, emit| {
+            // any whitespace may trail the data, but only ASCII whitespace may
+            // appear within it or before the padding
+            let end = match padding.is_empty() {
+                true => body
+                    .iter()
+                    .rposition(|(c, _)| !c.is_whitespace())
+                    .map_or(0, |i| i + 1),
+                false => body.len(),
+            };
+
+            let mut invalid: Option<Range<usize>> = None;
+            for (c, c_span) in &body[..end] {
+                match (charset::is_base64_ws(*c), invalid.as_mut()) {
+                    (false, Some(run)) if run.end == c_span.start => run.end = c_span.end,
+                    (false, _) => {
+                        if let Some(run) = invalid.replace(c_span.clone()) {
+                            emit(
+                                Simple::custom(run, "invalid character in data")
+                                    .with_label(LABEL_DATA),
+                            )
+                        }
+                    }
+                    (true, _) => {}
+                }
+            }
+            if let Some(run) = invalid {
+                emit(Simple::custom(run, "invalid character in data").with_label(LABEL_DATA))
+            }
+
+            // as `PemData::strip`, wiping the chars parsed once copied
+            let size = body.iter().map(|(c, _)| c.len_utf8()).sum::<usize>() + padding.len();
+            let mut data = Zeroizing::new(String::with_capacity(size));
+            data.extend(body.iter().map(|(c, _)| *c).filter(|c| !c.is_whitespace()));
+            data.push_str(&padding);
+            body.iter_mut().for_each(|(c, _)| c.zeroize());
+
+            if !PemData::validate_padding(&data[..data.len() - padding.len()], &padding) {
+                emit(
+                    Simple::custom(span, "invalid number of padding characters")
+                        .with_label(LABEL_PADDING),
+                )
+            }
+
+            data
+        },
+    )
+}
+
+fn pem_block() -> impl Parser<char, PemParts, Error = Simple<char>> {
+    pem_header()
+        .then_ignore(whitespace())
+        .then(pem_data())
+        .then_ignore(whitespace())
+        .then(pem_footer())
+        .validate(|((header_label, data), footer_label), span, emit| {
+            // header and footer labels must match
+            if header_label != footer_label {
+                emit(Simple::custom(
+                    span,
+                    "header and footer labels do not match",
+                ))
+            }
+            (header_label, data, footer_label)
+        })
+}
+
+// The chars of the input, spanned in bytes rather than in chars as with
+// `Stream::from(&str)`, so that spans need no converting
+fn stream(input: &str) -> CharStream<'_> {
+    let end = input.len();
+    Stream::from_iter(
+        end..end,
+        Box::new(input.char_indices().map(|(i, c)| (c, i..i + c.len_utf8()))),
+    )
+}
+
+// Parses a PEM block at the start of the input, returning its labels and data
+// along with its span in bytes, excluding the surrounding whitespace.
+fn pem_parts(input: &str) -> Result<(PemParts, Range<usize>), Vec<PemRejection>> {
+    whitespace()
+        .ignore_then(pem_block().map_with_span(|block, span| (block, span)))
+        .parse(stream(input))
+        .map_err(|errors| errors.into_iter().map(PemRejection::from).collect())
+}
+
+fn pem_from((header_label, data, footer_label): PemParts) -> Result<Pem, PemErr> {
+    let raw = Pem::format_pem_str(&header_label, &data);
+    Pem::from(raw, header_label, data, footer_label)
+}
+
+// Lists every reason the input was rejected, or returns the PEM if it was not
+pub fn diagnose(input: &str) -> Result<Pem, Vec<PemRejection>> {
+    let (parts, span) = pem_parts(input)?;
+
+    match pem_from(parts) {
+        Ok(my_pem) => Ok(my_pem),
+        Err(_) => Err(vec![PemRejection {
+            reason: PemRejectReason::Unknown,
+            span,
+            message: String::from("unable to create PEM"),
+        }]),
+    }
+}
+
+pub fn parse(input: &str) -> Result<Pem, DetectorError> {
+    match pem_parts(input) {
+        Ok((parts, _span)) => match pem_from(parts) {
+            Ok(my_pem) => Ok(my_pem),
+            Err(_) => Err(DetectorError {
+                kind: DetectorErrorKind::Unknown,
+            }),
+        },
+        Err(_) => Err(DetectorError {
+            kind: DetectorErrorKind::NoMatch,
+        }),
+    }
+}
+
+// Where the block starting the input ends, at the latest. Neither labels nor
+// data contain a '-', so a block ends with the third run of dashes after those
+// opening its header: closing the header, opening the footer, closing it.
+fn block_end(input: &str) -> usize {
+    let bytes = input.as_bytes();
+    let mut end = BOUNDARY_BEGIN_HEADER.len().min(bytes.len());
+    for _ in 0..3 {
+        match bytes[end..].iter().position(|b| *b == b'-') {
+            Some(dash) => end += dash,
+            None => return bytes.len(),
+        }
+        end += bytes[end..].iter().take_while(|b| **b == b'-').count();
+    }
+    end
+}
+
+// Parses a PEM block at the start of the input, returning it along with its
+// span in bytes. Only the block is parsed, rather than the rest of the input,
+// as a candidate is tried at every header.
+fn pem_at(input: &str) -> Option<(Pem, Range<usize>)> {
+    let (parts, span) = pem_parts(&input[..block_end(input)]).ok()?;
+    let found = pem_from(parts).ok()?.found_as(&input[span.clone()]);
+    Some((found, span))
+}
+
+pub struct PemDetector;
+
+impl Detector for PemDetector {
+    type Finding = Pem;
+
+    fn kind(&self) -> FindingKind {
+        FindingKind::PemPrivateKey
+    }
+
+    fn backend(&self) -> Backend {
+        Backend::Chumsky
+    }
+
+    fn detect<'a>(&'a self, input: &'a [u8]) -> Box<dyn Iterator<Item = Detection<Pem>> + 'a> {
+        Box::new(scan::scan(input, &scan::PEM_TRIGGERS, pem_at))
+    }
+}
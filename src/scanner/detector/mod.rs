@@ -1,4 +1,14 @@
+pub mod backend;
 pub mod chumsky;
+pub mod error;
 pub mod pest;
+pub mod scan;
+pub mod traits;
 pub mod vectorscan;
 pub mod winnow;
+
+pub use backend::{Backend, DetectorSet};
+pub use traits::{Detection, Detector};
+
+#[cfg(test)]
+pub mod test;
@@ -1 +1,45 @@
+use crate::scanner::detector::error::{DetectorError, DetectorErrorKind};
+use crate::scanner::detector::pest::grammar::{Rule, SecretParser};
+use crate::scanner::detector::scan;
+use crate::scanner::detector::traits::{Detection, Detector};
+use crate::scanner::detector::Backend;
+use crate::scanner::finding::FindingKind;
+use pest::Parser;
+use std::ops::Range;
 
+pub fn parse(input: &str) -> Result<String, DetectorError> {
+    if input.len() != 20 {
+        return Err(DetectorError {
+            kind: DetectorErrorKind::InvalidSize,
+        });
+    }
+
+    match SecretParser::parse(Rule::aws_api_key, input) {
+        Ok(pairs) => Ok(pairs.as_str().to_owned()),
+        Err(_) => Err(DetectorError {
+            kind: DetectorErrorKind::NoMatch,
+        }),
+    }
+}
+
+fn api_key_at(input: &str) -> Option<(String, Range<usize>)> {
+    scan::aws_api_key_at(input, |candidate| parse(candidate).ok())
+}
+
+pub struct ApiKeyDetector;
+
+impl Detector for ApiKeyDetector {
+    type Finding = String;
+
+    fn kind(&self) -> FindingKind {
+        FindingKind::AwsApiKey
+    }
+
+    fn backend(&self) -> Backend {
+        Backend::Pest
+    }
+
+    fn detect<'a>(&'a self, input: &'a [u8]) -> Box<dyn Iterator<Item = Detection<String>> + 'a> {
+        Box::new(scan::scan(input, &scan::AWS_API_KEY_TRIGGERS, api_key_at))
+    }
+}
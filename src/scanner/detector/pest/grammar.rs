@@ -0,0 +1,5 @@
+use pest_derive::Parser;
+
+#[derive(Parser)]
+#[grammar = "scanner/detector/pest/grammar.pest"]
+pub struct SecretParser;
@@ -1,4 +1,5 @@
 #[allow(dead_code)]
 pub mod aws;
+pub mod grammar;
 #[allow(dead_code)]
 pub mod private_key;
@@ -1 +1,94 @@
+use crate::scanner::detector::error::{DetectorError, DetectorErrorKind};
+use crate::scanner::detector::pest::grammar::{Rule, SecretParser};
+use crate::scanner::detector::scan;
+use crate::scanner::detector::traits::{Detection, Detector};
+use crate::scanner::detector::Backend;
+use crate::scanner::finding::private_key::pem::{Pem, PemData};
+use crate::scanner::finding::FindingKind;
+use pest::Parser;
+use std::ops::Range;
 
+// Detector for PEM Format as specified in RFC-7468, see grammar.pest
+
+fn no_match() -> DetectorError {
+    DetectorError {
+        kind: DetectorErrorKind::NoMatch,
+    }
+}
+
+// Parses a PEM block at the start of the input, returning it along with its
+// span, excluding the surrounding whitespace.
+fn pem(input: &str) -> Result<(Pem, Range<usize>), DetectorError> {
+    let block = match SecretParser::parse(Rule::pem, input) {
+        Ok(mut pairs) => pairs
+            .next()
+            .and_then(|pem| pem.into_inner().next())
+            .ok_or_else(no_match)?,
+        Err(_) => return Err(no_match()),
+    };
+    let span = block.as_span().start()..block.as_span().end();
+
+    let (mut header_label, mut data, mut padding, mut footer_label) = ("", "", "", "");
+    for pair in block.into_inner() {
+        match pair.as_rule() {
+            Rule::header => header_label = pair.into_inner().as_str(),
+            Rule::data => data = pair.as_str(),
+            Rule::padding => padding = pair.as_str(),
+            Rule::footer => footer_label = pair.into_inner().as_str(),
+            _ => {}
+        }
+    }
+
+    // header and footer labels must match
+    if header_label != footer_label {
+        return Err(no_match());
+    }
+
+    // strip all whitespace from data component
+    let data_str = PemData::strip(data, padding);
+
+    if !PemData::validate_padding(&data_str[..data_str.len() - padding.len()], padding) {
+        // invalid number of padding characters
+        return Err(no_match());
+    }
+
+    let raw = Pem::format_pem_str(header_label, &data_str);
+
+    match Pem::from(
+        raw,
+        header_label.to_string(),
+        data_str,
+        footer_label.to_string(),
+    ) {
+        Ok(my_pem) => Ok((my_pem, span)),
+        Err(_) => Err(DetectorError {
+            kind: DetectorErrorKind::Unknown,
+        }),
+    }
+}
+
+pub fn parse(input: &str) -> Result<Pem, DetectorError> {
+    pem(input).map(|(my_pem, _span)| my_pem)
+}
+
+pub struct PemDetector;
+
+impl Detector for PemDetector {
+    type Finding = Pem;
+
+    fn kind(&self) -> FindingKind {
+        FindingKind::PemPrivateKey
+    }
+
+    fn backend(&self) -> Backend {
+        Backend::Pest
+    }
+
+    fn detect<'a>(&'a self, input: &'a [u8]) -> Box<dyn Iterator<Item = Detection<Pem>> + 'a> {
+        Box::new(scan::scan(input, &scan::PEM_TRIGGERS, |text| {
+            pem(text)
+                .ok()
+                .map(|(found, span)| (found.found_as(&text[span.clone()]), span))
+        }))
+    }
+}
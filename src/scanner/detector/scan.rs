@@ -0,0 +1,114 @@
+use crate::scanner::detector::traits::Detection;
+use crate::scanner::finding::private_key::pem::BOUNDARY_BEGIN_HEADER;
+use std::ops::Range;
+
+// Scanning of arbitrary buffers. Parsers only recognise a finding at the start
+// of their input, so the buffer is searched for the offsets a finding may start
+// at (its triggers, e.g. "-----BEGIN " for PEM) and the parser is tried at each
+// of them in turn. Every offset is tried, even those within an earlier finding,
+// so that overlapping candidates are all reported.
+//
+// A finding is always valid UTF-8, so the buffer is split into its valid UTF-8
+// runs and each run is scanned on its own.
+
+pub const AWS_API_KEY_LEN: usize = 20;
+pub const AWS_API_KEY_TRIGGERS: [&str; 4] = ["AKIA", "ABIA", "ACCA", "ASIA"];
+pub const PEM_TRIGGERS: [&str; 1] = [BOUNDARY_BEGIN_HEADER];
+
+// Parses a finding at the start of the input, returning it along with its span
+pub type ParseAt<F> = fn(&str) -> Option<(F, Range<usize>)>;
+
+// Byte offsets of the start of each line, to find the location of a span
+pub struct LineIndex {
+    starts: Vec<usize>,
+}
+
+impl LineIndex {
+    pub fn new(input: &[u8]) -> LineIndex {
+        let starts = std::iter::once(0)
+            .chain(
+                input
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, b)| **b == b'\n')
+                    .map(|(i, _)| i + 1),
+            )
+            .collect();
+
+        LineIndex { starts }
+    }
+
+    // Line and column of the offset, both starting at 1. The column is counted
+    // in bytes, as the input may not be valid UTF-8.
+    pub fn location(&self, offset: usize) -> (usize, usize) {
+        let line = self.starts.partition_point(|start| *start <= offset);
+        (line, offset - self.starts[line - 1] + 1)
+    }
+
+    pub fn detection<F>(&self, finding: F, span: Range<usize>) -> Detection<F> {
+        let (line, column) = self.location(span.start);
+        Detection {
+            finding,
+            span,
+            line,
+            column,
+        }
+    }
+}
+
+// Column of the offset counted in UTF-16 code units, as editors (and SARIF)
+// count them, given its column in bytes. Bytes which are not valid UTF-8 are
+// counted as replacement characters.
+pub fn utf16_column(input: &[u8], offset: usize, column: usize) -> usize {
+    let line = &input[offset + 1 - column..offset];
+    String::from_utf8_lossy(line).encode_utf16().count() + 1
+}
+
+// Valid UTF-8 runs of the input, along with their offset
+fn utf8_runs(input: &[u8]) -> impl Iterator<Item = (usize, &str)> {
+    input.utf8_chunks().scan(0, |offset, chunk| {
+        let run = (*offset, chunk.valid());
+        *offset += chunk.valid().len() + chunk.invalid().len();
+        Some(run)
+    })
+}
+
+// Offsets of the text at which any of the triggers start
+fn candidates<'a>(text: &'a str, triggers: &'a [&str]) -> impl Iterator<Item = usize> + 'a {
+    let bytes = text.as_bytes();
+    (0..bytes.len()).filter(move |i| {
+        triggers
+            .iter()
+            .any(|t| bytes[*i..].starts_with(t.as_bytes()))
+    })
+}
+
+// Finds every finding within the input, trying the parser at each trigger
+pub fn scan<'a, F: 'a>(
+    input: &'a [u8],
+    triggers: &'a [&str],
+    parse_at: ParseAt<F>,
+) -> impl Iterator<Item = Detection<F>> + 'a {
+    let index = LineIndex::new(input);
+
+    utf8_runs(input)
+        .flat_map(move |(offset, run)| {
+            candidates(run, triggers).filter_map(move |start| {
+                parse_at(&run[start..]).map(|(finding, span)| {
+                    let start = offset + start;
+                    (finding, start + span.start..start + span.end)
+                })
+            })
+        })
+        .map(move |(finding, span)| index.detection(finding, span))
+}
+
+// Parses an AWS API Key at the start of the input, with a parser only accepting
+// a key of exactly `AWS_API_KEY_LEN` bytes.
+pub fn aws_api_key_at(
+    input: &str,
+    parse: impl Fn(&str) -> Option<String>,
+) -> Option<(String, Range<usize>)> {
+    let candidate = input.get(..AWS_API_KEY_LEN)?;
+    parse(candidate).map(|key| (key, 0..AWS_API_KEY_LEN))
+}
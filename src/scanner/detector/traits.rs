@@ -0,0 +1,51 @@
+use crate::scanner::detector::backend::Backend;
+use crate::scanner::finding::FindingKind;
+use std::ops::Range;
+
+// A single finding produced by a detector, along with the byte span of the
+// input it was found in, and the line & column (both starting at 1) it starts at.
+#[derive(Clone, Debug)]
+pub struct Detection<F> {
+    pub finding: F,
+    pub span: Range<usize>,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl<F> Detection<F> {
+    pub fn map<G>(self, op: impl FnOnce(F) -> G) -> Detection<G> {
+        Detection {
+            finding: op(self.finding),
+            span: self.span,
+            line: self.line,
+            column: self.column,
+        }
+    }
+}
+
+// Common interface implemented by every parser backend, so that a scan can
+// swap backends without changing the calling code.
+pub trait Detector: Send + Sync {
+    type Finding;
+
+    // The kind of finding this detector produces
+    fn kind(&self) -> FindingKind;
+
+    // The parser backend this detector is built with
+    fn backend(&self) -> Backend;
+
+    // Stable identifier, shared by all backends detecting the same finding kind
+    fn id(&self) -> &'static str {
+        self.kind().id()
+    }
+
+    fn name(&self) -> &'static str {
+        self.kind().name()
+    }
+
+    // Finds every occurrence within the input, which may contain any bytes
+    fn detect<'a>(
+        &'a self,
+        input: &'a [u8],
+    ) -> Box<dyn Iterator<Item = Detection<Self::Finding>> + 'a>;
+}
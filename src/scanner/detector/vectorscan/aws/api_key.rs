@@ -1 +1,63 @@
+use crate::scanner::detector::error::{DetectorError, DetectorErrorKind};
+use crate::scanner::detector::scan::LineIndex;
+use crate::scanner::detector::traits::{Detection, Detector};
+use crate::scanner::detector::vectorscan::database::{self, PatternId};
+use crate::scanner::detector::Backend;
+use crate::scanner::finding::FindingKind;
 
+pub fn parse(input: &str) -> Result<String, DetectorError> {
+    if input.len() != 20 {
+        return Err(DetectorError {
+            kind: DetectorErrorKind::InvalidSize,
+        });
+    }
+
+    let matches = match database::scan(input.as_bytes()) {
+        Ok(matches) => matches,
+        Err(_) => {
+            return Err(DetectorError {
+                kind: DetectorErrorKind::Unknown,
+            })
+        }
+    };
+
+    match matches
+        .iter()
+        .any(|m| m.id == PatternId::AwsApiKey && m.span == (0..input.len()))
+    {
+        true => Ok(input.to_owned()),
+        false => Err(DetectorError {
+            kind: DetectorErrorKind::NoMatch,
+        }),
+    }
+}
+
+pub struct ApiKeyDetector;
+
+impl Detector for ApiKeyDetector {
+    type Finding = String;
+
+    fn kind(&self) -> FindingKind {
+        FindingKind::AwsApiKey
+    }
+
+    fn backend(&self) -> Backend {
+        Backend::Vectorscan
+    }
+
+    fn detect<'a>(&'a self, input: &'a [u8]) -> Box<dyn Iterator<Item = Detection<String>> + 'a> {
+        // the pattern only matches ASCII, so every match is valid UTF-8
+        let index = LineIndex::new(input);
+        let matches = database::detect(input);
+
+        Box::new(
+            matches
+                .into_iter()
+                .filter(|m| m.id == PatternId::AwsApiKey)
+                .filter_map(move |m| {
+                    let key = std::str::from_utf8(&input[m.span.clone()]).ok()?;
+                    Some(index.detection(key.to_owned(), m.span))
+                }),
+        )
+    }
+}
@@ -0,0 +1,361 @@
+use std::ffi::CString;
+use std::ops::Range;
+use std::os::raw::{c_char, c_int, c_uint, c_ulonglong, c_void};
+use std::ptr;
+use std::sync::{Mutex, OnceLock};
+use vectorscan::{
+    hs_alloc_scratch, hs_close_stream, hs_compile_error_t, hs_compile_multi, hs_database_t,
+    hs_error_t, hs_free_compile_error, hs_free_database, hs_free_scratch, hs_open_stream, hs_scan,
+    hs_scan_stream, hs_scratch_t, hs_stream_t, HS_FLAG_SOM_LEFTMOST, HS_MODE_BLOCK,
+    HS_MODE_SOM_HORIZON_LARGE, HS_MODE_STREAM, HS_SUCCESS,
+};
+
+// Every pattern of every detector is compiled into a single database, so that
+// a buffer is scanned once no matter how many detectors there are. Matches are
+// reported with the id of the pattern and the offsets it was found at; it is
+// then up to each detector to build (and validate) findings from them.
+
+// any printable character except "-" and lowercase letters, with single spaces
+// allowed between words, as per the winnow `pem_label` parser
+macro_rules! pem_label {
+    () => {
+        r"[\x21-\x2c\x2e-\x60\x7b-\x7e]+(?: [\x21-\x2c\x2e-\x60\x7b-\x7e]+)*"
+    };
+}
+
+// Largest input scanned in a single call, as lengths are given as 32 bits
+const MAX_SCAN_LEN: usize = c_uint::MAX as usize;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PatternId {
+    AwsApiKey = 1,
+    PemHeader = 2,
+    PemFooter = 3,
+}
+
+impl PatternId {
+    pub const ALL: [PatternId; 3] = [
+        PatternId::AwsApiKey,
+        PatternId::PemHeader,
+        PatternId::PemFooter,
+    ];
+
+    pub fn expression(&self) -> &'static str {
+        match self {
+            PatternId::AwsApiKey => r"(?:AKIA|ABIA|ACCA|ASIA)[A-Za-z0-9+/]{16}",
+            PatternId::PemHeader => concat!("-----BEGIN ", pem_label!(), "-----"),
+            PatternId::PemFooter => concat!("-----END ", pem_label!(), "-----"),
+        }
+    }
+
+    fn from_id(id: c_uint) -> Option<PatternId> {
+        PatternId::ALL.into_iter().find(|p| *p as c_uint == id)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum VectorscanErr {
+    CompileFailed,
+    ScratchFailed,
+    ScanFailed,
+    InvalidMode,
+    InputTooLarge,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mode {
+    Block,
+    Stream,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Match {
+    pub id: PatternId,
+    pub span: Range<usize>,
+}
+
+struct Scratch(*mut hs_scratch_t);
+
+// scratch space may be moved between threads, as long as it is only used by
+// one at a time
+unsafe impl Send for Scratch {}
+
+impl Drop for Scratch {
+    fn drop(&mut self) {
+        unsafe { hs_free_scratch(self.0) };
+    }
+}
+
+pub struct Database {
+    db: *mut hs_database_t,
+    mode: Mode,
+    scratch: Mutex<Vec<Scratch>>,
+}
+
+// a compiled database is immutable, and each scan is given its own scratch
+unsafe impl Send for Database {}
+unsafe impl Sync for Database {}
+
+unsafe extern "C" fn on_match(
+    id: c_uint,
+    from: c_ulonglong,
+    to: c_ulonglong,
+    _flags: c_uint,
+    context: *mut c_void,
+) -> c_int {
+    let matches = &mut *(context as *mut Vec<Match>);
+    if let Some(id) = PatternId::from_id(id) {
+        matches.push(Match {
+            id,
+            span: from as usize..to as usize,
+        });
+    }
+    0 // continue scanning
+}
+
+fn check(result: hs_error_t, err: VectorscanErr) -> Result<(), VectorscanErr> {
+    match result == HS_SUCCESS as hs_error_t {
+        true => Ok(()),
+        false => Err(err),
+    }
+}
+
+fn sorted(mut matches: Vec<Match>) -> Vec<Match> {
+    matches.sort_by_key(|m| (m.span.start, m.span.end, m.id as c_uint));
+    matches
+}
+
+impl Database {
+    pub fn new(mode: Mode) -> Result<Database, VectorscanErr> {
+        let expressions = PatternId::ALL
+            .iter()
+            .map(|p| CString::new(p.expression()))
+            .collect::<Result<Vec<CString>, _>>()
+            .map_err(|_| VectorscanErr::CompileFailed)?;
+        let expression_ptrs: Vec<*const c_char> = expressions.iter().map(|e| e.as_ptr()).collect();
+        let flags: Vec<c_uint> = vec![HS_FLAG_SOM_LEFTMOST; expressions.len()];
+        let ids: Vec<c_uint> = PatternId::ALL.iter().map(|p| *p as c_uint).collect();
+
+        let hs_mode = match mode {
+            Mode::Block => HS_MODE_BLOCK,
+            Mode::Stream => HS_MODE_STREAM | HS_MODE_SOM_HORIZON_LARGE,
+        };
+
+        let mut db: *mut hs_database_t = ptr::null_mut();
+        let mut error: *mut hs_compile_error_t = ptr::null_mut();
+        let result = unsafe {
+            hs_compile_multi(
+                expression_ptrs.as_ptr(),
+                flags.as_ptr(),
+                ids.as_ptr(),
+                expressions.len() as c_uint,
+                hs_mode,
+                ptr::null(),
+                &mut db,
+                &mut error,
+            )
+        };
+
+        if !error.is_null() {
+            unsafe { hs_free_compile_error(error) };
+        }
+        check(result, VectorscanErr::CompileFailed)?;
+
+        Ok(Database {
+            db,
+            mode,
+            scratch: Mutex::new(Vec::new()),
+        })
+    }
+
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    fn take_scratch(&self) -> Result<Scratch, VectorscanErr> {
+        if let Some(scratch) = self.scratch.lock().ok().and_then(|mut pool| pool.pop()) {
+            return Ok(scratch);
+        }
+
+        let mut scratch: *mut hs_scratch_t = ptr::null_mut();
+        check(
+            unsafe { hs_alloc_scratch(self.db, &mut scratch) },
+            VectorscanErr::ScratchFailed,
+        )?;
+        Ok(Scratch(scratch))
+    }
+
+    fn return_scratch(&self, scratch: Scratch) {
+        if let Ok(mut pool) = self.scratch.lock() {
+            pool.push(scratch);
+        }
+    }
+
+    // Scans the whole input in one pass, returning the matches of every pattern
+    // ordered by their offsets.
+    pub fn scan(&self, input: &[u8]) -> Result<Vec<Match>, VectorscanErr> {
+        if self.mode != Mode::Block {
+            return Err(VectorscanErr::InvalidMode);
+        }
+        let length = c_uint::try_from(input.len()).map_err(|_| VectorscanErr::InputTooLarge)?;
+
+        let scratch = self.take_scratch()?;
+        let mut matches: Vec<Match> = Vec::new();
+        let result = unsafe {
+            hs_scan(
+                self.db,
+                input.as_ptr() as *const c_char,
+                length,
+                0,
+                scratch.0,
+                Some(on_match),
+                &mut matches as *mut Vec<Match> as *mut c_void,
+            )
+        };
+        self.return_scratch(scratch);
+        check(result, VectorscanErr::ScanFailed)?;
+
+        Ok(sorted(matches))
+    }
+
+    pub fn open_stream(&self) -> Result<Stream<'_>, VectorscanErr> {
+        if self.mode != Mode::Stream {
+            return Err(VectorscanErr::InvalidMode);
+        }
+
+        let scratch = self.take_scratch()?;
+        let mut stream: *mut hs_stream_t = ptr::null_mut();
+        if let Err(e) = check(
+            unsafe { hs_open_stream(self.db, 0, &mut stream) },
+            VectorscanErr::ScanFailed,
+        ) {
+            self.return_scratch(scratch);
+            return Err(e);
+        }
+
+        Ok(Stream {
+            database: self,
+            stream,
+            scratch: Some(scratch),
+        })
+    }
+}
+
+impl Drop for Database {
+    fn drop(&mut self) {
+        if let Ok(mut pool) = self.scratch.lock() {
+            pool.clear();
+        }
+        unsafe { hs_free_database(self.db) };
+    }
+}
+
+// A stream scan, where the input is given in chunks. Offsets of matches are
+// relative to the start of the stream, and a match may span several chunks.
+pub struct Stream<'a> {
+    database: &'a Database,
+    stream: *mut hs_stream_t,
+    scratch: Option<Scratch>,
+}
+
+impl Stream<'_> {
+    fn scratch(&self) -> *mut hs_scratch_t {
+        self.scratch.as_ref().map_or(ptr::null_mut(), |s| s.0)
+    }
+
+    pub fn scan(&mut self, chunk: &[u8]) -> Result<Vec<Match>, VectorscanErr> {
+        let length = c_uint::try_from(chunk.len()).map_err(|_| VectorscanErr::InputTooLarge)?;
+
+        let mut matches: Vec<Match> = Vec::new();
+        check(
+            unsafe {
+                hs_scan_stream(
+                    self.stream,
+                    chunk.as_ptr() as *const c_char,
+                    length,
+                    0,
+                    self.scratch(),
+                    Some(on_match),
+                    &mut matches as *mut Vec<Match> as *mut c_void,
+                )
+            },
+            VectorscanErr::ScanFailed,
+        )?;
+
+        Ok(sorted(matches))
+    }
+
+    // Ends the stream, returning any matches only known once the input has ended
+    pub fn close(mut self) -> Result<Vec<Match>, VectorscanErr> {
+        let mut matches: Vec<Match> = Vec::new();
+        let result = unsafe {
+            hs_close_stream(
+                self.stream,
+                self.scratch(),
+                Some(on_match),
+                &mut matches as *mut Vec<Match> as *mut c_void,
+            )
+        };
+        self.stream = ptr::null_mut();
+        check(result, VectorscanErr::ScanFailed)?;
+
+        Ok(sorted(matches))
+    }
+}
+
+impl Drop for Stream<'_> {
+    fn drop(&mut self) {
+        if !self.stream.is_null() {
+            unsafe { hs_close_stream(self.stream, self.scratch(), None, ptr::null_mut()) };
+        }
+        if let Some(scratch) = self.scratch.take() {
+            self.database.return_scratch(scratch);
+        }
+    }
+}
+
+// Databases are compiled once, on first use
+pub fn database(mode: Mode) -> Result<&'static Database, VectorscanErr> {
+    static BLOCK: OnceLock<Result<Database, VectorscanErr>> = OnceLock::new();
+    static STREAM: OnceLock<Result<Database, VectorscanErr>> = OnceLock::new();
+
+    let database = match mode {
+        Mode::Block => &BLOCK,
+        Mode::Stream => &STREAM,
+    };
+
+    match database.get_or_init(|| Database::new(mode)) {
+        Ok(db) => Ok(db),
+        Err(e) => Err(*e),
+    }
+}
+
+// Scans the input with every pattern in one pass. Inputs too large to scan in
+// a single call are scanned as a stream instead, with the same offsets.
+pub fn scan(input: &[u8]) -> Result<Vec<Match>, VectorscanErr> {
+    match input.len() <= MAX_SCAN_LEN {
+        true => database(Mode::Block)?.scan(input),
+        false => scan_pieces(input, MAX_SCAN_LEN),
+    }
+}
+
+// Scans the input for a detector, which cannot fail: an error, e.g. failing to
+// allocate scratch space, is logged and nothing is found
+pub fn detect(input: &[u8]) -> Vec<Match> {
+    scan(input).unwrap_or_else(|e| {
+        eprintln!("warning: vectorscan failed to scan the input: {:?}", e);
+        Vec::new()
+    })
+}
+
+// Scans the input as a stream of pieces of at most `piece_len` bytes
+pub fn scan_pieces(input: &[u8], piece_len: usize) -> Result<Vec<Match>, VectorscanErr> {
+    let mut stream = database(Mode::Stream)?.open_stream()?;
+    let mut matches: Vec<Match> = Vec::new();
+    for piece in input.chunks(piece_len) {
+        matches.extend(stream.scan(piece)?);
+    }
+    matches.extend(stream.close()?);
+
+    Ok(sorted(matches))
+}
@@ -1,4 +1,8 @@
 #[allow(dead_code)]
 pub mod aws;
+pub mod database;
 #[allow(dead_code)]
 pub mod private_key;
+
+#[cfg(test)]
+pub mod test;
@@ -1 +1,158 @@
+use crate::scanner::common::charset;
+use crate::scanner::detector::error::{DetectorError, DetectorErrorKind};
+use crate::scanner::detector::scan::LineIndex;
+use crate::scanner::detector::traits::{Detection, Detector};
+use crate::scanner::detector::vectorscan::database::{self, Match, PatternId};
+use crate::scanner::detector::Backend;
+use crate::scanner::finding::private_key::pem::{
+    Pem, PemData, BOUNDARY_BEGIN_FOOTER, BOUNDARY_BEGIN_HEADER, BOUNDARY_END,
+};
+use crate::scanner::finding::FindingKind;
+use std::ops::Range;
+use zeroize::Zeroizing;
 
+// Detector for PEM Format as specified in RFC-7468:
+// https://www.rfc-editor.org/rfc/rfc7468
+//
+// The pattern database only finds the header and footer boundaries. The data
+// between them is checked here, accepting the same input as the winnow
+// detector, before the labels and data go through `Pem` construction.
+
+fn no_match() -> DetectorError {
+    DetectorError {
+        kind: DetectorErrorKind::NoMatch,
+    }
+}
+
+fn boundary_label<'a>(boundary: &'a str, begin: &str) -> &'a str {
+    &boundary[begin.len()..boundary.len() - BOUNDARY_END.len()]
+}
+
+// Checks the text between the header and footer, returning the data with its
+// whitespace removed.
+pub fn pem_data(body: &str) -> Result<Zeroizing<String>, DetectorError> {
+    // whitespace may follow the header
+    let body = body.trim_start();
+
+    let data_len = body
+        .find(|c: char| !charset::is_base64_ws(c))
+        .unwrap_or(body.len());
+    if data_len == 0 {
+        return Err(no_match());
+    }
+    let (data, rest) = body.split_at(data_len);
+
+    let padding_len = rest
+        .find(|c: char| c != charset::BASE64_SYMBOL_PADDING)
+        .unwrap_or(rest.len());
+    let (padding, rest) = rest.split_at(padding_len);
+
+    // only whitespace may precede the footer
+    if !rest.chars().all(char::is_whitespace) {
+        return Err(no_match());
+    }
+
+    // strip all whitespace from data component
+    let data_str = PemData::strip(data, padding);
+
+    match PemData::validate_padding(&data_str[..data_str.len() - padding.len()], padding) {
+        true => Ok(data_str),
+        // invalid number of padding characters
+        false => Err(no_match()),
+    }
+}
+
+// Builds a PEM from a header match and the first footer match following it
+pub fn pem_from_matches(
+    input: &[u8],
+    header: &Match,
+    matches: &[Match],
+) -> Result<(Pem, Range<usize>), DetectorError> {
+    let footer = matches
+        .iter()
+        .find(|m| m.id == PatternId::PemFooter && m.span.start >= header.span.end)
+        .ok_or_else(no_match)?;
+
+    let text = |span: Range<usize>| std::str::from_utf8(&input[span]).map_err(|_| no_match());
+
+    let header_label = boundary_label(text(header.span.clone())?, BOUNDARY_BEGIN_HEADER);
+    let footer_label = boundary_label(text(footer.span.clone())?, BOUNDARY_BEGIN_FOOTER);
+    let data = pem_data(text(header.span.end..footer.span.start)?)?;
+
+    // header and footer labels must match
+    if header_label != footer_label {
+        return Err(no_match());
+    }
+
+    let raw = Pem::format_pem_str(header_label, &data);
+    let span = header.span.start..footer.span.end;
+    let found = text(span.clone())?;
+
+    match Pem::from(
+        raw,
+        header_label.to_string(),
+        data,
+        footer_label.to_string(),
+    ) {
+        Ok(my_pem) => Ok((my_pem.found_as(found), span)),
+        Err(_) => Err(DetectorError {
+            kind: DetectorErrorKind::Unknown,
+        }),
+    }
+}
+
+// Finds a PEM block at the start of the input, after any whitespace
+fn pem(input: &str) -> Result<(Pem, Range<usize>), DetectorError> {
+    let start = input.len() - input.trim_start().len();
+
+    let matches = database::scan(input.as_bytes()).map_err(|_| DetectorError {
+        kind: DetectorErrorKind::Unknown,
+    })?;
+
+    let header = matches
+        .iter()
+        .find(|m| m.id == PatternId::PemHeader && m.span.start == start)
+        .ok_or_else(no_match)?;
+
+    pem_from_matches(input.as_bytes(), header, &matches)
+}
+
+pub fn parse(input: &str) -> Result<Pem, DetectorError> {
+    pem(input).map(|(my_pem, _span)| my_pem)
+}
+
+pub struct PemDetector;
+
+impl Detector for PemDetector {
+    type Finding = Pem;
+
+    fn kind(&self) -> FindingKind {
+        FindingKind::PemPrivateKey
+    }
+
+    fn backend(&self) -> Backend {
+        Backend::Vectorscan
+    }
+
+    fn detect<'a>(&'a self, input: &'a [u8]) -> Box<dyn Iterator<Item = Detection<Pem>> + 'a> {
+        let index = LineIndex::new(input);
+        let matches = database::detect(input);
+        let of = |id: PatternId| -> Vec<Match> {
+            matches.iter().filter(|m| m.id == id).cloned().collect()
+        };
+        let (headers, footers) = (of(PatternId::PemHeader), of(PatternId::PemFooter));
+
+        // the matches are ordered, so the footers before a header are before the
+        // next header too: they are walked once, rather than once per header
+        let mut next_footer = 0;
+        Box::new(headers.into_iter().filter_map(move |header| {
+            next_footer += footers[next_footer..]
+                .iter()
+                .take_while(|f| f.span.start < header.span.end)
+                .count();
+            pem_from_matches(input, &header, &footers[next_footer..])
+                .ok()
+                .map(|(found, span)| index.detection(found, span))
+        }))
+    }
+}
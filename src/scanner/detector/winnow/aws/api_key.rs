@@ -1,6 +1,11 @@
 use crate::scanner::common::charset;
-use crate::scanner::detector::winnow::error::DetectorError;
-use crate::scanner::detector::winnow::error::DetectorErrorKind;
+use crate::scanner::detector::error::DetectorError;
+use crate::scanner::detector::error::DetectorErrorKind;
+use crate::scanner::detector::scan;
+use crate::scanner::detector::traits::{Detection, Detector};
+use crate::scanner::detector::Backend;
+use crate::scanner::finding::FindingKind;
+use std::ops::Range;
 use winnow::combinator::alt;
 use winnow::token::take_while;
 use winnow::{PResult, Parser};
@@ -27,3 +32,25 @@ pub fn parse(input: &str) -> Result<String, DetectorError> {
         }),
     }
 }
+
+fn api_key_at(input: &str) -> Option<(String, Range<usize>)> {
+    scan::aws_api_key_at(input, |candidate| parse(candidate).ok())
+}
+
+pub struct ApiKeyDetector;
+
+impl Detector for ApiKeyDetector {
+    type Finding = String;
+
+    fn kind(&self) -> FindingKind {
+        FindingKind::AwsApiKey
+    }
+
+    fn backend(&self) -> Backend {
+        Backend::Winnow
+    }
+
+    fn detect<'a>(&'a self, input: &'a [u8]) -> Box<dyn Iterator<Item = Detection<String>> + 'a> {
+        Box::new(scan::scan(input, &scan::AWS_API_KEY_TRIGGERS, api_key_at))
+    }
+}
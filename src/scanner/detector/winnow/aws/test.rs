@@ -1,18 +1,24 @@
-use crate::scanner::detector::winnow::aws;
-use crate::scanner::detector::winnow::error::DetectorErrorKind;
+use crate::scanner::detector::error::DetectorErrorKind;
+use crate::scanner::detector::test::AWS_PARSERS;
+use crate::scanner::detector::Backend;
+use crate::scanner::finding::FindingKind;
+
+// The cases are run against every backend
 
 pub fn assert_valid_key(test_case: &str) {
-    let mut input = test_case;
-    let result = aws::api_key::parse(&mut input);
-    assert_eq!(result.is_err(), false);
-    assert_eq!(test_case, result.unwrap().to_owned());
+    for (backend, parse) in AWS_PARSERS {
+        let result = parse(test_case);
+        assert_eq!(result.is_err(), false, "{}", backend.name());
+        assert_eq!(test_case, result.unwrap(), "{}", backend.name());
+    }
 }
 
 pub fn assert_invalid_key(test_case: &str, err: DetectorErrorKind) {
-    let mut input = test_case;
-    let result = aws::api_key::parse(&mut input);
-    assert_eq!(result.is_err(), true);
-    assert_eq!(result.unwrap_err().kind, err);
+    for (backend, parse) in AWS_PARSERS {
+        let result = parse(test_case);
+        assert_eq!(result.is_err(), true, "{}", backend.name());
+        assert_eq!(result.unwrap_err().kind, err, "{}", backend.name());
+    }
 }
 
 #[test]
@@ -37,3 +43,32 @@ fn fp_invalid_length() {
         DetectorErrorKind::InvalidSize,
     );
 }
+
+#[test]
+fn tp_detector() {
+    let input = r#"AKIAXR2OBLUTM8DTZV7F"#;
+
+    for backend in Backend::ALL {
+        let detector = backend.aws_api_key();
+        let detections: Vec<_> = detector.detect(input.as_bytes()).collect();
+        assert_eq!(detections.len(), 1, "{}", backend.name());
+        assert_eq!(detections[0].finding, input);
+        assert_eq!(detections[0].span, 0..20);
+        assert_eq!(detector.id(), FindingKind::AwsApiKey.id());
+    }
+}
+
+#[test]
+fn fp_detector() {
+    let input = r#"ABCDXR2OBLUTM8DTZV7F"#;
+
+    for backend in Backend::ALL {
+        let detector = backend.aws_api_key();
+        assert_eq!(
+            detector.detect(input.as_bytes()).count(),
+            0,
+            "{}",
+            backend.name()
+        );
+    }
+}
@@ -1,6 +1,3 @@
-#[allow(dead_code)]
-pub mod error;
-
 #[allow(dead_code)]
 pub mod aws;
 
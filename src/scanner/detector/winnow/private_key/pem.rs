@@ -1,12 +1,18 @@
 use crate::scanner::common::charset;
-use crate::scanner::detector::winnow::error::{DetectorError, DetectorErrorKind};
+use crate::scanner::detector::error::{DetectorError, DetectorErrorKind};
+use crate::scanner::detector::scan;
+use crate::scanner::detector::traits::{Detection, Detector};
+use crate::scanner::detector::Backend;
 use crate::scanner::finding::private_key::pem::{
     Pem, PemData, BOUNDARY_BEGIN_FOOTER, BOUNDARY_BEGIN_HEADER, BOUNDARY_END,
 };
+use crate::scanner::finding::FindingKind;
+use std::ops::Range;
 use winnow::combinator::{opt, preceded, terminated};
 use winnow::error::{ContextError, ErrMode};
 use winnow::token::take_while;
 use winnow::{PResult, Parser};
+use zeroize::Zeroizing;
 
 // Detector for PEM Format as specified in RFC-7468:
 // https://www.rfc-editor.org/rfc/rfc7468
@@ -36,7 +42,7 @@ pub fn pem_footer<'a>(input: &mut &'a str) -> PResult<&'a str> {
     pem_boundary(BOUNDARY_BEGIN_FOOTER, BOUNDARY_END, input)
 }
 
-pub fn pem_data(input: &mut &str) -> PResult<String> {
+pub fn pem_data(input: &mut &str) -> PResult<Zeroizing<String>> {
     match (
         take_while(1.., charset::BASE64_WS),
         Parser::void(opt(take_while(1.., charset::ASCII_WHITESPACE))),
@@ -47,11 +53,9 @@ pub fn pem_data(input: &mut &str) -> PResult<String> {
     {
         Ok((data, (), padding, ())) => {
             // strip all whitespace from data component
-            let mut data_str = String::from(data);
-            data_str.retain(|c| !c.is_whitespace());
+            let data_str = PemData::strip(data, padding);
 
-            if PemData::validate_padding(data_str.as_str(), padding) {
-                data_str.push_str(padding);
+            if PemData::validate_padding(&data_str[..data_str.len() - padding.len()], padding) {
                 Ok(data_str)
             } else {
                 // invalid number of padding characters
@@ -71,7 +75,11 @@ pub fn pem_label<'a>(input: &mut &'a str) -> PResult<&'a str> {
 }
 
 pub fn parse(input: &str) -> Result<Pem, DetectorError> {
-    match (pem_header, pem_data, pem_footer).parse_next(&mut &*input) {
+    pem(&mut &*input)
+}
+
+fn pem(input: &mut &str) -> Result<Pem, DetectorError> {
+    match (pem_header, pem_data, pem_footer).parse_next(input) {
         Ok((header_label, data, footer_label)) => {
             // header and footer labels must match
             if header_label != footer_label {
@@ -80,7 +88,7 @@ pub fn parse(input: &str) -> Result<Pem, DetectorError> {
                 });
             }
 
-            let raw = Pem::format_pem_str(header_label.to_string(), data.to_string());
+            let raw = Pem::format_pem_str(header_label, &data);
 
             match Pem::from(
                 raw,
@@ -99,3 +107,30 @@ pub fn parse(input: &str) -> Result<Pem, DetectorError> {
         }),
     }
 }
+
+// Parses a PEM block at the start of the input, returning it along with its
+// span, excluding the whitespace following it.
+fn pem_at(input: &str) -> Option<(Pem, Range<usize>)> {
+    let mut remaining = input;
+    let found = pem(&mut remaining).ok()?;
+    let end = input[..input.len() - remaining.len()].trim_end().len();
+    Some((found.found_as(&input[..end]), 0..end))
+}
+
+pub struct PemDetector;
+
+impl Detector for PemDetector {
+    type Finding = Pem;
+
+    fn kind(&self) -> FindingKind {
+        FindingKind::PemPrivateKey
+    }
+
+    fn backend(&self) -> Backend {
+        Backend::Winnow
+    }
+
+    fn detect<'a>(&'a self, input: &'a [u8]) -> Box<dyn Iterator<Item = Detection<Pem>> + 'a> {
+        Box::new(scan::scan(input, &scan::PEM_TRIGGERS, pem_at))
+    }
+}
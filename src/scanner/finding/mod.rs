@@ -1 +1,59 @@
 pub mod private_key;
+
+use crate::scanner::finding::private_key::pem::Pem;
+
+// Finding types, numbered as per the "Finding Types" table in docs/README.md
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FindingKind {
+    AwsApiKey = 1,
+    PemPrivateKey = 2,
+}
+
+impl FindingKind {
+    pub const ALL: [FindingKind; 2] = [FindingKind::AwsApiKey, FindingKind::PemPrivateKey];
+
+    pub fn id(&self) -> &'static str {
+        match self {
+            FindingKind::AwsApiKey => "aws-api-key",
+            FindingKind::PemPrivateKey => "pem-private-key",
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            FindingKind::AwsApiKey => "AWS API Key",
+            FindingKind::PemPrivateKey => "PEM Private Key",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            FindingKind::AwsApiKey => "An Amazon AWS API Key credential",
+            FindingKind::PemPrivateKey => "A PEM formatted private key",
+        }
+    }
+}
+
+// A finding of any type, for code that handles more than one detector at a time
+#[derive(Clone, Debug)]
+pub enum Finding {
+    AwsApiKey(String),
+    Pem(Pem),
+}
+
+impl Finding {
+    pub fn kind(&self) -> FindingKind {
+        match self {
+            Finding::AwsApiKey(_) => FindingKind::AwsApiKey,
+            Finding::Pem(_) => FindingKind::PemPrivateKey,
+        }
+    }
+
+    // The secret as it was found in the input
+    pub fn secret(&self) -> &str {
+        match self {
+            Finding::AwsApiKey(key) => key,
+            Finding::Pem(pem) => &pem.found,
+        }
+    }
+}
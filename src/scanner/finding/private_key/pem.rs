@@ -1,5 +1,8 @@
+use crate::scanner::redact::MASK;
 use openssl::pkey::{PKey, Private};
 use openssl::x509::X509;
+use std::fmt;
+use zeroize::Zeroizing;
 
 pub const BOUNDARY_BEGIN_HEADER: &str = "-----BEGIN ";
 pub const BOUNDARY_BEGIN_FOOTER: &str = "-----END ";
@@ -26,9 +29,10 @@ pub enum PemStatus {
     Unknown,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+// Key material is held in buffers wiped when dropped, and left out of `Debug`
+#[derive(Clone, PartialEq)]
 pub struct PemData {
-    pub raw: String,
+    pub raw: Zeroizing<String>,
     padding_count: usize,
 }
 
@@ -41,7 +45,7 @@ pub enum PemErr {
 }
 
 impl PemData {
-    pub fn from(input: String) -> Result<PemData, PemErr> {
+    pub fn from(input: Zeroizing<String>) -> Result<PemData, PemErr> {
         match input.len() {
             0..1 => Err(PemErr::InvalidInput),
             len => Ok(PemData {
@@ -76,20 +80,45 @@ impl PemData {
         }
     }
 
-    pub fn format(input: String) -> String {
-        input
-            .chars()
-            .collect::<Vec<char>>()
-            .chunks(64)
-            .map(|chunk| chunk.iter().collect::<String>())
-            .collect::<Vec<String>>()
-            .join("\n")
+    // The data without whitespace, followed by the padding. Like the other
+    // buffers holding key material, it is sized up front, as growing it would
+    // leave copies behind which are not wiped.
+    pub fn strip(data: &str, padding: &str) -> Zeroizing<String> {
+        let mut stripped = Zeroizing::new(String::with_capacity(data.len() + padding.len()));
+        stripped.extend(data.chars().filter(|c| !c.is_whitespace()));
+        stripped.push_str(padding);
+        stripped
+    }
+
+    // Wrapped at 64 characters a line
+    pub fn format(input: &str) -> Zeroizing<String> {
+        let mut formatted = Zeroizing::new(String::with_capacity(input.len() + input.len() / 64));
+        for (i, c) in input.chars().enumerate() {
+            if i > 0 && i % 64 == 0 {
+                formatted.push('\n');
+            }
+            formatted.push(c);
+        }
+        formatted
     }
 }
 
-#[derive(Clone, Debug)]
+impl fmt::Debug for PemData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PemData")
+            .field("raw", &MASK)
+            .field("found", &MASK)
+            .field("padding_count", &self.padding_count)
+            .finish()
+    }
+}
+
+// The private key is wiped by openssl when freed, as are the buffers it is
+// encoded to by `to_pem`
+#[derive(Clone)]
 pub struct Pem {
-    pub raw: String,
+    pub raw: Zeroizing<String>, // re-encoded from the labels & data, for openssl
+    pub found: Zeroizing<String>, // as it was found in the input
     pub header_label: String,
     pub data: PemData,
     pub footer_label: String,
@@ -103,15 +132,16 @@ pub struct Pem {
 
 impl Pem {
     pub fn from(
-        raw: String,
+        raw: Zeroizing<String>,
         header_label: String,
-        data: String,
+        data: Zeroizing<String>,
         footer_label: String,
     ) -> Result<Pem, PemErr> {
         Ok(Pem {
+            found: raw.clone(),
             raw,
             header_label,
-            data: PemData::from(data.to_string())?,
+            data: PemData::from(data)?,
             footer_label,
             kind: None,
             status: None,
@@ -120,7 +150,16 @@ impl Pem {
         })
     }
 
-    pub fn to_string(&self) -> Result<String, PemErr> {
+    // Records the text the PEM was parsed from, which `raw` may differ from in
+    // its whitespace and line lengths
+    pub fn found_as(mut self, text: &str) -> Pem {
+        self.found = Zeroizing::new(String::from(text));
+        self
+    }
+
+    // The key (or certificate) re-encoded as PEM, i.e. the secret itself. To
+    // show a PEM without its key material, use its `Display` form instead.
+    pub fn to_pem(&self) -> Result<Zeroizing<String>, PemErr> {
         if self.private_key.is_none() && self.cert.is_none() {
             self.get_kind()?;
         }
@@ -133,18 +172,18 @@ impl Pem {
             Some(_) => self.private_key.clone().unwrap().private_key_to_pem_pkcs8(),
         } {
             Ok(secret) => {
-                let pem_result = String::from_utf8(secret);
-                if pem_result.is_err() {
-                    return Err(PemErr::Unknown); // TODO: better error handling
+                let secret = Zeroizing::new(secret);
+                match std::str::from_utf8(&secret) {
+                    Ok(pem) => Ok(Zeroizing::new(String::from(pem))),
+                    Err(_) => Err(PemErr::Unknown), // TODO: better error handling
                 }
-                Ok(pem_result.unwrap())
             }
             Err(_) => Err(PemErr::Unknown),
         }
     }
 
-    fn data_formatted(&self) -> String {
-        PemData::format(self.data.raw.to_owned())
+    fn data_formatted(&self) -> Zeroizing<String> {
+        PemData::format(&self.data.raw)
     }
 
     pub fn header(&self) -> String {
@@ -189,6 +228,36 @@ impl Pem {
         }
     }
 
+    // Checks the PEM offline by its label, parsing it at most once. Only private
+    // keys OpenSSL reads without a passphrase, and certificates, can be checked.
+    pub fn check(&self) -> (PemStatus, Option<PemKind>) {
+        if let Some(status) = self.status {
+            return (status, self.kind);
+        }
+
+        if self.header_label != self.footer_label {
+            return (PemStatus::InvalidEnclosure, self.kind);
+        }
+
+        match self.header_label.as_str() {
+            "PRIVATE KEY" | "RSA PRIVATE KEY" | "DSA PRIVATE KEY" | "EC PRIVATE KEY" => {
+                match self.get_private_key() {
+                    Ok(pk) => (PemStatus::Valid, Some(Pem::key_kind(pk.id()))),
+                    Err(PemErr::UnknownKeyType) => (PemStatus::Unknown, self.kind),
+                    Err(_) => (PemStatus::Invalid, self.kind),
+                }
+            }
+            "CERTIFICATE" | "X509 CERTIFICATE" => match self.get_cert() {
+                Ok(_) => (PemStatus::Valid, Some(PemKind::Certificate)),
+                Err(_) => (PemStatus::Invalid, self.kind),
+            },
+            "CERTIFICATE REQUEST" | "NEW CERTIFICATE REQUEST" => {
+                (PemStatus::Unknown, Some(PemKind::CertSignRequest))
+            }
+            _ => (PemStatus::Unknown, self.kind),
+        }
+    }
+
     pub fn check_is_valid(&mut self) -> bool {
         match self.status {
             None => match self.validate() {
@@ -211,21 +280,22 @@ impl Pem {
         }
     }
 
-    pub fn format_pem_str(label: String, data: String) -> String {
-        let nl = String::from("\n");
-        let the_label = label.as_str();
-        [
-            BOUNDARY_BEGIN_HEADER,
-            the_label,
-            BOUNDARY_END,
-            nl.as_str(),
-            PemData::format(data).as_str(),
-            nl.as_str(),
-            BOUNDARY_BEGIN_FOOTER,
-            the_label,
-            BOUNDARY_END,
-        ]
-        .concat()
+    // `concat` sizes the buffer up front, so it is never reallocated
+    pub fn format_pem_str(label: &str, data: &str) -> Zeroizing<String> {
+        Zeroizing::new(
+            [
+                BOUNDARY_BEGIN_HEADER,
+                label,
+                BOUNDARY_END,
+                "\n",
+                &PemData::format(data),
+                "\n",
+                BOUNDARY_BEGIN_FOOTER,
+                label,
+                BOUNDARY_END,
+            ]
+            .concat(),
+        )
     }
 
     pub fn get_cert(&self) -> Result<X509, PemErr> {
@@ -252,20 +322,7 @@ impl Pem {
 
     pub fn get_kind(&self) -> Result<PemKind, PemErr> {
         match self.get_private_key() {
-            Ok(pk) => Ok(match pk.id() {
-                openssl::pkey::Id::RSA
-                | openssl::pkey::Id::DSA
-                | openssl::pkey::Id::DH
-                | openssl::pkey::Id::EC => PemKind::AsymmetricKey,
-                openssl::pkey::Id::HMAC => PemKind::Hash,
-                openssl::pkey::Id::CMAC => PemKind::Mac,
-                openssl::pkey::Id::HKDF => PemKind::KeyDerivationFunction,
-                openssl::pkey::Id::ED25519
-                | openssl::pkey::Id::ED448
-                | openssl::pkey::Id::X25519
-                | openssl::pkey::Id::X448 => PemKind::EllipticCurve,
-                _ => PemKind::Unknown,
-            }),
+            Ok(pk) => Ok(Pem::key_kind(pk.id())),
             Err(_) => {
                 return match self.get_cert() {
                     Ok(_) => Ok(PemKind::Certificate),
@@ -275,6 +332,23 @@ impl Pem {
         }
     }
 
+    fn key_kind(id: openssl::pkey::Id) -> PemKind {
+        match id {
+            openssl::pkey::Id::RSA
+            | openssl::pkey::Id::DSA
+            | openssl::pkey::Id::DH
+            | openssl::pkey::Id::EC => PemKind::AsymmetricKey,
+            openssl::pkey::Id::HMAC => PemKind::Hash,
+            openssl::pkey::Id::CMAC => PemKind::Mac,
+            openssl::pkey::Id::HKDF => PemKind::KeyDerivationFunction,
+            openssl::pkey::Id::ED25519
+            | openssl::pkey::Id::ED448
+            | openssl::pkey::Id::X25519
+            | openssl::pkey::Id::X448 => PemKind::EllipticCurve,
+            _ => PemKind::Unknown,
+        }
+    }
+
     pub fn get_private_key(&self) -> Result<PKey<Private>, PemErr> {
         Pem::private_key_from_pem(&self)
     }
@@ -336,3 +410,26 @@ impl Pem {
         }
     }
 }
+
+// The labels of the PEM, with the key material masked
+impl fmt::Display for Pem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let footer = [BOUNDARY_BEGIN_FOOTER, &self.footer_label, BOUNDARY_END].concat();
+        write!(f, "{}{}{}", self.header(), MASK, footer)
+    }
+}
+
+impl fmt::Debug for Pem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Pem")
+            .field("raw", &MASK)
+            .field("header_label", &self.header_label)
+            .field("data", &self.data)
+            .field("footer_label", &self.footer_label)
+            .field("kind", &self.kind)
+            .field("status", &self.status)
+            .field("private_key", &self.private_key.as_ref().map(|_| MASK))
+            .field("cert", &self.cert)
+            .finish()
+    }
+}
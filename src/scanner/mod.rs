@@ -2,4 +2,6 @@
 pub mod common;
 pub mod detector;
 pub mod finding;
+pub mod redact;
+pub mod report;
 pub mod source;
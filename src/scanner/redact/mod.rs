@@ -0,0 +1,72 @@
+#[cfg(test)]
+pub mod test;
+
+use openssl::sha::sha256;
+
+// How a secret is shown wherever a finding is written out (reports, logs),
+// so that reporting a leaked secret does not leak it again. Every reporter
+// redacts by default; the secret itself is only shown when asked for.
+
+pub const MASK: &str = "****";
+pub const HASH_PREFIX: &str = "sha256:";
+pub const DEFAULT_KEEP_FIRST: usize = 4;
+pub const DEFAULT_KEEP_LAST: usize = 0;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Redaction {
+    Mask,                                  // nothing of the secret, not even its length
+    Partial { first: usize, last: usize }, // e.g. the prefix telling the kind of AWS key
+    Hash,                                  // to match findings of the same secret
+    Raw,                                   // the secret itself
+}
+
+impl Default for Redaction {
+    fn default() -> Redaction {
+        Redaction::Partial {
+            first: DEFAULT_KEEP_FIRST,
+            last: DEFAULT_KEEP_LAST,
+        }
+    }
+}
+
+impl Redaction {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Redaction::Mask => "mask",
+            Redaction::Partial { .. } => "partial",
+            Redaction::Hash => "hash",
+            Redaction::Raw => "raw",
+        }
+    }
+
+    pub fn apply(&self, secret: &str) -> String {
+        match *self {
+            Redaction::Mask => String::from(MASK),
+            Redaction::Partial { first, last } => partial(secret, first, last),
+            Redaction::Hash => hash(secret),
+            Redaction::Raw => String::from(secret),
+        }
+    }
+}
+
+// Keeps the first and last characters, unless they would make up the whole
+// secret, in which case it is masked entirely
+fn partial(secret: &str, first: usize, last: usize) -> String {
+    let chars: Vec<char> = secret.chars().collect();
+    if first.saturating_add(last) >= chars.len() {
+        return String::from(MASK);
+    }
+
+    let mut redacted: String = chars[..first].iter().collect();
+    redacted.push_str(MASK);
+    redacted.extend(&chars[chars.len() - last..]);
+    redacted
+}
+
+pub fn hash(secret: &str) -> String {
+    let digest: String = sha256(secret.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    [HASH_PREFIX, &digest].concat()
+}
@@ -0,0 +1,56 @@
+use crate::scanner::redact::{self, Redaction, HASH_PREFIX, MASK};
+
+const AWS_API_KEY: &str = "AKIAXR2OBLUTM8DTZV7F";
+
+#[test]
+fn tp_redact_default() {
+    assert_eq!(Redaction::default().apply(AWS_API_KEY), "AKIA****");
+}
+
+#[test]
+fn tp_redact_mask() {
+    assert_eq!(Redaction::Mask.apply(AWS_API_KEY), MASK);
+    assert_eq!(Redaction::Mask.apply(""), MASK);
+}
+
+#[test]
+fn tp_redact_partial() {
+    let redaction = Redaction::Partial { first: 4, last: 2 };
+    assert_eq!(redaction.apply(AWS_API_KEY), "AKIA****7F");
+    assert_eq!(redaction.apply("clé-secrète"), "clé-****te");
+
+    let redaction = Redaction::Partial { first: 0, last: 3 };
+    assert_eq!(redaction.apply(AWS_API_KEY), "****V7F");
+}
+
+#[test]
+fn fp_redact_partial_whole_secret() {
+    // never keeps so much that the whole secret is shown
+    let redaction = Redaction::Partial { first: 4, last: 4 };
+    assert_eq!(redaction.apply("abcdefgh"), MASK);
+    assert_eq!(redaction.apply("abc"), MASK);
+
+    let redaction = Redaction::Partial {
+        first: usize::MAX,
+        last: usize::MAX,
+    };
+    assert_eq!(redaction.apply(AWS_API_KEY), MASK);
+}
+
+#[test]
+fn tp_redact_hash() {
+    let hash = Redaction::Hash.apply(AWS_API_KEY);
+    assert!(hash.starts_with(HASH_PREFIX));
+    assert_eq!(hash.len(), HASH_PREFIX.len() + 64);
+    assert_eq!(hash, redact::hash(AWS_API_KEY));
+    assert_ne!(hash, redact::hash("AKIAXR2OBLUTM8DTZV7G"));
+    assert_eq!(
+        redact::hash(""),
+        "sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+    );
+}
+
+#[test]
+fn tp_redact_raw() {
+    assert_eq!(Redaction::Raw.apply(AWS_API_KEY), AWS_API_KEY);
+}
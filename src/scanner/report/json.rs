@@ -0,0 +1,110 @@
+use crate::scanner::redact::Redaction;
+use crate::scanner::report::schema::{FindingRecord, SCHEMA_VERSION};
+use crate::scanner::report::traits::Reporter;
+use crate::scanner::source::SourceFinding;
+use serde::Serialize;
+use std::io::{self, Write};
+
+// Findings as JSON, following the schema in `report::schema`. Both reporters
+// write each finding as it is reported rather than holding on to them, as a
+// scan of a long history may produce millions of findings:
+//
+// - JSON, a single document: {"schema_version":1,"findings":[...]}
+// - JSON Lines, a finding per line, each with its own "schema_version"
+
+#[derive(Serialize)]
+struct Line<'a> {
+    schema_version: u32,
+    #[serde(flatten)]
+    record: &'a FindingRecord,
+}
+
+pub struct JsonReporter<W: Write> {
+    writer: W,
+    redaction: Redaction,
+    findings: usize,
+}
+
+impl<W: Write> JsonReporter<W> {
+    pub fn new(writer: W) -> JsonReporter<W> {
+        JsonReporter::with_redaction(writer, Redaction::default())
+    }
+
+    pub fn with_redaction(writer: W, redaction: Redaction) -> JsonReporter<W> {
+        JsonReporter {
+            writer,
+            redaction,
+            findings: 0,
+        }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    fn start(&mut self) -> io::Result<()> {
+        write!(
+            self.writer,
+            "{{\"schema_version\":{},\"findings\":[",
+            SCHEMA_VERSION
+        )
+    }
+}
+
+impl<W: Write> Reporter for JsonReporter<W> {
+    fn report(&mut self, finding: &SourceFinding) -> io::Result<()> {
+        match self.findings {
+            0 => self.start()?,
+            _ => write!(self.writer, ",")?,
+        }
+        writeln!(self.writer)?;
+        let record = FindingRecord::new(finding, self.redaction);
+        serde_json::to_writer(&mut self.writer, &record)?;
+        self.findings += 1;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        match self.findings {
+            0 => self.start()?,
+            _ => writeln!(self.writer)?,
+        }
+        writeln!(self.writer, "]}}")?;
+        self.writer.flush()
+    }
+}
+
+pub struct JsonLinesReporter<W: Write> {
+    writer: W,
+    redaction: Redaction,
+}
+
+impl<W: Write> JsonLinesReporter<W> {
+    pub fn new(writer: W) -> JsonLinesReporter<W> {
+        JsonLinesReporter::with_redaction(writer, Redaction::default())
+    }
+
+    pub fn with_redaction(writer: W, redaction: Redaction) -> JsonLinesReporter<W> {
+        JsonLinesReporter { writer, redaction }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write> Reporter for JsonLinesReporter<W> {
+    fn report(&mut self, finding: &SourceFinding) -> io::Result<()> {
+        let record = FindingRecord::new(finding, self.redaction);
+        let line = Line {
+            schema_version: SCHEMA_VERSION,
+            record: &record,
+        };
+        serde_json::to_writer(&mut self.writer, &line)?;
+        writeln!(self.writer)
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
@@ -0,0 +1,10 @@
+pub mod json;
+pub mod sarif;
+pub mod schema;
+pub mod text;
+pub mod traits;
+
+pub use traits::Reporter;
+
+#[cfg(test)]
+pub mod test;
@@ -0,0 +1,344 @@
+use crate::scanner::finding::FindingKind;
+use crate::scanner::redact::Redaction;
+use crate::scanner::report::text::STDIN_PATH;
+use crate::scanner::report::traits::Reporter;
+use crate::scanner::source::git::cache;
+use crate::scanner::source::git::client::GitUrlKind;
+use crate::scanner::source::{Provenance, SourceFinding};
+use openssl::sha::Sha256;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{self, Write};
+use std::path::Path;
+
+// Static Analysis Results Interchange Format, as read by code scanning
+// dashboards, see: https://docs.oasis-open.org/sarif/sarif/v2.1.0/sarif-v2.1.0.html
+//
+// Every finding kind is a rule of the tool, and every finding a result of a
+// single run. A SARIF log is one document, so results are held until `finish`.
+//
+// Columns are counted in UTF-16 code units, the SARIF default. The commits the
+// findings were made in are listed as the revisions of the repository scanned.
+
+pub const VERSION: &str = "2.1.0";
+pub const SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+pub const FINGERPRINT: &str = "rst/v1";
+const TOOL_NAME: &str = "rst";
+const LEVEL: &str = "error";
+const COLUMN_KIND: &str = "utf16CodeUnits";
+
+#[derive(Serialize)]
+struct Log {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    version: &'static str,
+    runs: Vec<Run>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Run {
+    tool: Tool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    version_control_provenance: Vec<VersionControlDetails>,
+    column_kind: &'static str,
+    results: Vec<SarifResult>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct VersionControlDetails {
+    repository_uri: String,
+    revision_id: String,
+}
+
+#[derive(Serialize)]
+struct Tool {
+    driver: Driver,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Driver {
+    name: &'static str,
+    version: &'static str,
+    rules: Vec<Rule>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Rule {
+    id: &'static str,
+    short_description: Message,
+    full_description: Message,
+    default_configuration: Configuration,
+}
+
+#[derive(Serialize)]
+struct Configuration {
+    level: &'static str,
+}
+
+#[derive(Serialize)]
+struct Message {
+    text: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifResult {
+    rule_id: &'static str,
+    rule_index: usize,
+    level: &'static str,
+    message: Message,
+    locations: Vec<Location>,
+    fingerprints: BTreeMap<&'static str, String>,
+    #[serde(skip_serializing_if = "Properties::is_empty")]
+    properties: Properties,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Location {
+    physical_location: PhysicalLocation,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PhysicalLocation {
+    artifact_location: ArtifactLocation,
+    region: Region,
+}
+
+#[derive(Serialize)]
+struct ArtifactLocation {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    uri: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<Message>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Region {
+    start_line: usize,
+    start_column: usize,
+}
+
+// Where the finding came from, beyond its location
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Properties {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    commit: Option<Commit>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    layer: Option<String>,
+}
+
+impl Properties {
+    fn is_empty(&self) -> bool {
+        self.commit.is_none() && self.layer.is_none()
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Commit {
+    sha: String,
+    author: String,
+    email: String,
+    timestamp: i64,
+}
+
+pub struct SarifReporter<W: Write> {
+    writer: W,
+    redaction: Redaction,
+    results: Vec<SarifResult>,
+    repository: Option<String>,
+    revisions: BTreeSet<String>,
+}
+
+impl<W: Write> SarifReporter<W> {
+    pub fn new(writer: W) -> SarifReporter<W> {
+        SarifReporter::with_redaction(writer, Redaction::default())
+    }
+
+    pub fn with_redaction(writer: W, redaction: Redaction) -> SarifReporter<W> {
+        SarifReporter {
+            writer,
+            redaction,
+            results: Vec::new(),
+            repository: None,
+            revisions: BTreeSet::new(),
+        }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+// Percent-encodes a path as a relative URI reference, leaving "/" and the
+// "!/" separating archive members as they are
+pub fn uri(path: &str) -> String {
+    let mut uri = String::with_capacity(path.len());
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' => uri.push(byte as char),
+            b'-' | b'.' | b'_' | b'~' | b'/' | b'!' | b'$' | b'&' | b'\'' | b'(' | b')' | b'*'
+            | b'+' | b',' | b';' | b'=' | b':' | b'@' => uri.push(byte as char),
+            _ => uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    uri
+}
+
+// Stable across scans, so that dashboards track a secret in a file as the same
+// alert no matter the line it moves to. The secret itself is only hashed.
+pub fn fingerprint(kind: FindingKind, path: Option<&str>, secret: &str) -> String {
+    let mut hasher = Sha256::new();
+    for part in [kind.id(), path.unwrap_or(STDIN_PATH), secret] {
+        hasher.update(part.as_bytes());
+        hasher.update(&[0]);
+    }
+    hasher
+        .finish()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+// The repository as a URI: a remote's URL without its credentials, with the
+// scp-like syntax as an ssh:// URL, or a local path as a file:// URL
+pub fn repository_uri(kind: GitUrlKind, url: &str) -> String {
+    let url = cache::strip_userinfo(url);
+    match kind {
+        GitUrlKind::FILESYSTEM => {
+            let path = url.strip_prefix("file://").unwrap_or(&url);
+            let path = std::path::absolute(Path::new(path)).unwrap_or_else(|_| path.into());
+            format!("file://{}", uri(&path.to_string_lossy()))
+        }
+        _ if url.contains("://") => url,
+        _ => format!("ssh://{}", url.replacen(':', "/", 1)),
+    }
+}
+
+fn artifact_location(provenance: &Provenance) -> ArtifactLocation {
+    match &provenance.path {
+        Some(path) => ArtifactLocation {
+            uri: Some(uri(path)),
+            description: None,
+        },
+        None => ArtifactLocation {
+            uri: None,
+            description: Some(Message {
+                text: String::from(STDIN_PATH),
+            }),
+        },
+    }
+}
+
+fn rules() -> Vec<Rule> {
+    FindingKind::ALL
+        .iter()
+        .map(|kind| Rule {
+            id: kind.id(),
+            short_description: Message {
+                text: String::from(kind.name()),
+            },
+            full_description: Message {
+                text: String::from(kind.description()),
+            },
+            default_configuration: Configuration { level: LEVEL },
+        })
+        .collect()
+}
+
+impl<W: Write> Reporter for SarifReporter<W> {
+    fn repository(&mut self, uri: &str) {
+        self.repository = Some(String::from(uri));
+    }
+
+    fn report(&mut self, finding: &SourceFinding) -> io::Result<()> {
+        let kind = finding.finding.kind();
+        let provenance = &finding.provenance;
+        if let Some(commit) = &provenance.commit {
+            self.revisions.insert(commit.id.clone());
+        }
+
+        self.results.push(SarifResult {
+            rule_id: kind.id(),
+            rule_index: FindingKind::ALL
+                .iter()
+                .position(|k| *k == kind)
+                .unwrap_or(0),
+            level: LEVEL,
+            message: Message {
+                text: format!(
+                    "{} found: {}",
+                    kind.name(),
+                    self.redaction.apply(finding.finding.secret())
+                ),
+            },
+            locations: vec![Location {
+                physical_location: PhysicalLocation {
+                    artifact_location: artifact_location(provenance),
+                    region: Region {
+                        start_line: finding.line,
+                        start_column: finding.column_utf16,
+                    },
+                },
+            }],
+            fingerprints: BTreeMap::from([(
+                FINGERPRINT,
+                fingerprint(kind, provenance.path.as_deref(), finding.finding.secret()),
+            )]),
+            properties: Properties {
+                commit: provenance.commit.as_ref().map(|commit| Commit {
+                    sha: commit.id.clone(),
+                    author: commit.author.clone(),
+                    email: commit.email.clone(),
+                    timestamp: commit.timestamp,
+                }),
+                layer: provenance.layer.clone(),
+            },
+        });
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        // a revision is only meaningful along with its repository
+        let revisions = std::mem::take(&mut self.revisions);
+        let version_control_provenance = match &self.repository {
+            Some(repository) => revisions
+                .into_iter()
+                .map(|revision_id| VersionControlDetails {
+                    repository_uri: repository.clone(),
+                    revision_id,
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+
+        let log = Log {
+            schema: SCHEMA,
+            version: VERSION,
+            runs: vec![Run {
+                tool: Tool {
+                    driver: Driver {
+                        name: TOOL_NAME,
+                        version: env!("CARGO_PKG_VERSION"),
+                        rules: rules(),
+                    },
+                },
+                version_control_provenance,
+                column_kind: COLUMN_KIND,
+                results: std::mem::take(&mut self.results),
+            }],
+        };
+
+        serde_json::to_writer_pretty(&mut self.writer, &log)?;
+        writeln!(self.writer)?;
+        self.writer.flush()
+    }
+}
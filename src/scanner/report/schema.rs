@@ -0,0 +1,170 @@
+use crate::scanner::finding::private_key::pem::{PemKind, PemStatus};
+use crate::scanner::finding::Finding;
+use crate::scanner::redact::Redaction;
+use crate::scanner::source::{CommitInfo, SourceFinding};
+use serde::{Deserialize, Serialize};
+
+// Machine readable form of a finding, shared by the JSON and JSON Lines
+// reporters. Consumers rely on its fields, so any change which renames or
+// removes a field, or changes its meaning, must bump the schema version.
+// Fields may be added without a bump.
+
+pub const SCHEMA_VERSION: u32 = 1;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FindingRecord {
+    pub detector: String,     // stable detector id, e.g. "aws-api-key"
+    pub finding_type: String, // e.g. "AWS API Key"
+    pub secret: String,       // redacted, unless raw secrets were asked for
+    pub redaction: String,    // how the secret was redacted, e.g. "partial" or "raw"
+    pub location: Location,
+    pub provenance: Provenance,
+    pub validation: Option<Validation>, // none if the finding type has no offline validation
+    pub metadata: Metadata,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Location {
+    pub path: Option<String>, // none for stdin
+    pub line: usize,
+    pub column: usize, // in bytes
+    pub span: Span,
+}
+
+// Byte offsets of the finding within the content scanned, which is the whole
+// file unless the source scans part of it (e.g. the lines added by a commit)
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Provenance {
+    pub source: String, // e.g. "git", "filesystem"
+    pub commit: Option<Commit>,
+    pub layer: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Commit {
+    pub id: String,
+    pub author: String,
+    pub email: String,
+    pub timestamp: i64, // seconds since the unix epoch
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Validation {
+    Valid,
+    Invalid,
+    InvalidEnclosure,
+    Unknown,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Metadata {
+    pub pem_kind: Option<KeyKind>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyKind {
+    SymmetricKey,
+    AsymmetricKey,
+    EllipticCurve,
+    Mac,
+    KeyDerivationFunction,
+    Hash,
+    Certificate,
+    CertSignRequest,
+    Unknown,
+}
+
+impl From<PemStatus> for Validation {
+    fn from(status: PemStatus) -> Validation {
+        match status {
+            PemStatus::Valid => Validation::Valid,
+            PemStatus::Invalid => Validation::Invalid,
+            PemStatus::InvalidEnclosure => Validation::InvalidEnclosure,
+            PemStatus::Unknown => Validation::Unknown,
+        }
+    }
+}
+
+impl From<PemKind> for KeyKind {
+    fn from(kind: PemKind) -> KeyKind {
+        match kind {
+            PemKind::SymmetricKey => KeyKind::SymmetricKey,
+            PemKind::AsymmetricKey => KeyKind::AsymmetricKey,
+            PemKind::EllipticCurve => KeyKind::EllipticCurve,
+            PemKind::Mac => KeyKind::Mac,
+            PemKind::KeyDerivationFunction => KeyKind::KeyDerivationFunction,
+            PemKind::Hash => KeyKind::Hash,
+            PemKind::Certificate => KeyKind::Certificate,
+            PemKind::CertSignRequest => KeyKind::CertSignRequest,
+            PemKind::Unknown => KeyKind::Unknown,
+        }
+    }
+}
+
+impl From<&CommitInfo> for Commit {
+    fn from(commit: &CommitInfo) -> Commit {
+        Commit {
+            id: commit.id.clone(),
+            author: commit.author.clone(),
+            email: commit.email.clone(),
+            timestamp: commit.timestamp,
+        }
+    }
+}
+
+impl From<&SourceFinding> for FindingRecord {
+    fn from(finding: &SourceFinding) -> FindingRecord {
+        FindingRecord::new(finding, Redaction::default())
+    }
+}
+
+impl FindingRecord {
+    pub fn new(finding: &SourceFinding, redaction: Redaction) -> FindingRecord {
+        let kind = finding.finding.kind();
+        let provenance = &finding.provenance;
+
+        let (validation, metadata) = match &finding.finding {
+            Finding::AwsApiKey(_) => (None, Metadata::default()),
+            Finding::Pem(pem) => {
+                let (status, kind) = pem.check();
+                (
+                    Some(Validation::from(status)),
+                    Metadata {
+                        pem_kind: kind.map(KeyKind::from),
+                    },
+                )
+            }
+        };
+
+        FindingRecord {
+            detector: String::from(kind.id()),
+            finding_type: String::from(kind.name()),
+            secret: redaction.apply(finding.finding.secret()),
+            redaction: String::from(redaction.name()),
+            location: Location {
+                path: provenance.path.clone(),
+                line: finding.line,
+                column: finding.column,
+                span: Span {
+                    start: finding.span.start,
+                    end: finding.span.end,
+                },
+            },
+            provenance: Provenance {
+                source: String::from(provenance.kind.name()),
+                commit: provenance.commit.as_ref().map(Commit::from),
+                layer: provenance.layer.clone(),
+            },
+            validation,
+            metadata,
+        }
+    }
+}
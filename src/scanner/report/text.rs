@@ -0,0 +1,81 @@
+use crate::scanner::redact::Redaction;
+use crate::scanner::report::traits::Reporter;
+use crate::scanner::source::{Provenance, SourceFinding};
+use std::io::{self, Write};
+
+// Human readable output, a line per finding, e.g.:
+//
+//   AWS API Key AKIA**** at config/credentials:2:21 in commit 1a2b3c4d (Alice <alice@example.com>)
+
+const SHORT_ID_LEN: usize = 8;
+pub const STDIN_PATH: &str = "<stdin>";
+
+pub struct TextReporter<W: Write> {
+    writer: W,
+    redaction: Redaction,
+}
+
+impl<W: Write> TextReporter<W> {
+    pub fn new(writer: W) -> TextReporter<W> {
+        TextReporter::with_redaction(writer, Redaction::default())
+    }
+
+    pub fn with_redaction(writer: W, redaction: Redaction) -> TextReporter<W> {
+        TextReporter { writer, redaction }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+// Where the finding is, as path:line:column
+pub fn location(provenance: &Provenance, line: usize, column: usize) -> String {
+    let path = provenance.path.as_deref().unwrap_or(STDIN_PATH);
+    format!("{}:{}:{}", path, line, column)
+}
+
+fn context(provenance: &Provenance) -> Option<String> {
+    if let Some(commit) = &provenance.commit {
+        let id = &commit.id[..commit.id.len().min(SHORT_ID_LEN)];
+        return Some(format!(
+            "commit {} ({} <{}>)",
+            id, commit.author, commit.email
+        ));
+    }
+
+    provenance
+        .layer
+        .as_ref()
+        .map(|layer| format!("layer {}", layer))
+}
+
+impl<W: Write> Reporter for TextReporter<W> {
+    fn report(&mut self, finding: &SourceFinding) -> io::Result<()> {
+        let location = location(&finding.provenance, finding.line, finding.column);
+        // kept to a single line, should the raw secret span several
+        let secret = self.redaction.apply(finding.finding.secret());
+        let secret = secret.escape_debug();
+        match context(&finding.provenance) {
+            Some(context) => writeln!(
+                self.writer,
+                "{} {} at {} in {}",
+                finding.finding.kind().name(),
+                secret,
+                location,
+                context
+            ),
+            None => writeln!(
+                self.writer,
+                "{} {} at {}",
+                finding.finding.kind().name(),
+                secret,
+                location
+            ),
+        }
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
@@ -0,0 +1,15 @@
+use crate::scanner::source::SourceFinding;
+use std::io;
+
+// Common interface implemented by every output format. Findings are reported
+// as they are found, so that formats which can be streamed need not hold on to
+// them; `finish` is called once the scan is over.
+pub trait Reporter {
+    // The repository the findings come from, as a URI, if they come from one.
+    // Given before any finding is reported.
+    fn repository(&mut self, _uri: &str) {}
+
+    fn report(&mut self, finding: &SourceFinding) -> io::Result<()>;
+
+    fn finish(&mut self) -> io::Result<()>;
+}
@@ -0,0 +1,340 @@
+use crate::scanner::source::filesystem::client::is_binary;
+use crate::scanner::source::traits::{Chunk, Provenance, Source, SourceKind};
+use bzip2::read::MultiBzDecoder;
+use flate2::read::MultiGzDecoder;
+use std::cell::Cell;
+use std::fs;
+use std::io::{Cursor, Read};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread;
+use xz2::read::XzDecoder;
+use zip::result::ZipError;
+
+// Scanning of the files within an archive, such as a build artifact. Zip and
+// tar archives are opened, and gzip, bzip2 and xz streams decompressed, with
+// formats told apart by their magic bytes rather than by file extensions.
+// Archives within archives are opened in turn, up to a maximum depth, and the
+// path of each member is given through every archive it is in, e.g.:
+//
+//   outer.tar.gz!/inner.zip!/config/key.pem
+//
+// To guard against zip bombs, every byte decompressed is counted against both
+// a total and a multiple of the size of the outer archive; the scan is stopped
+// as soon as either is exceeded. Members are read on another thread, a few
+// files ahead of the caller.
+
+pub const SEPARATOR: &str = "!/";
+pub const MAX_DEPTH: usize = 4;
+pub const MAX_TOTAL_BYTES: u64 = 256 * 1024 * 1024;
+pub const MAX_RATIO: u64 = 100;
+const CHUNKS_AHEAD: usize = 16;
+
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+const ZIP_EMPTY_MAGIC: &[u8] = b"PK\x05\x06";
+const GZIP_MAGIC: &[u8] = b"\x1f\x8b";
+const BZIP2_MAGIC: &[u8] = b"BZh";
+const XZ_MAGIC: &[u8] = b"\xfd7zXZ\x00";
+const TAR_MAGIC: &[u8] = b"ustar";
+const TAR_MAGIC_OFFSET: usize = 257;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ArchiveErr {
+    NotFound,
+    ReadFailed,
+    UnknownFormat,
+    Corrupt,
+    Unsupported,   // e.g. a zip member compressed with bzip2, or encrypted
+    TooLarge,      // more than the total bytes were decompressed
+    RatioExceeded, // more than the ratio times the archive size were decompressed
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Format {
+    Zip,
+    Tar,
+    Gzip,
+    Bzip2,
+    Xz,
+}
+
+impl Format {
+    fn detect(content: &[u8]) -> Option<Format> {
+        let is_tar = content
+            .get(TAR_MAGIC_OFFSET..TAR_MAGIC_OFFSET + TAR_MAGIC.len())
+            .is_some_and(|magic| magic == TAR_MAGIC);
+
+        match content {
+            _ if content.starts_with(ZIP_MAGIC) || content.starts_with(ZIP_EMPTY_MAGIC) => {
+                Some(Format::Zip)
+            }
+            _ if content.starts_with(GZIP_MAGIC) => Some(Format::Gzip),
+            _ if content.starts_with(BZIP2_MAGIC) => Some(Format::Bzip2),
+            _ if content.starts_with(XZ_MAGIC) => Some(Format::Xz),
+            _ if is_tar => Some(Format::Tar),
+            _ => None,
+        }
+    }
+
+    // The decompressed stream, unless the format is an archive
+    fn decoder(self, content: &[u8]) -> Option<Box<dyn Read + '_>> {
+        match self {
+            Format::Gzip => Some(Box::new(MultiGzDecoder::new(content))),
+            Format::Bzip2 => Some(Box::new(MultiBzDecoder::new(content))),
+            Format::Xz => Some(Box::new(XzDecoder::new_multi_decoder(content))),
+            Format::Zip | Format::Tar => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ArchiveOptions {
+    pub max_depth: usize, // of archives nested within the outer archive
+    pub max_total_bytes: u64,
+    pub max_ratio: u64,
+}
+
+impl Default for ArchiveOptions {
+    fn default() -> ArchiveOptions {
+        ArchiveOptions {
+            max_depth: MAX_DEPTH,
+            max_total_bytes: MAX_TOTAL_BYTES,
+            max_ratio: MAX_RATIO,
+        }
+    }
+}
+
+// Bytes decompressed so far, and how many may be
+struct Budget {
+    used: Cell<u64>,
+    max_total_bytes: u64,
+    max_ratio_bytes: u64,
+    exceeded: Cell<Option<ArchiveErr>>,
+}
+
+impl Budget {
+    fn take(&self, count: u64) -> Result<(), ArchiveErr> {
+        let used = self.used.get() + count;
+        self.used.set(used);
+
+        let exceeded = match used {
+            _ if used > self.max_total_bytes => Some(ArchiveErr::TooLarge),
+            _ if used > self.max_ratio_bytes => Some(ArchiveErr::RatioExceeded),
+            _ => None,
+        };
+        match exceeded {
+            Some(e) => {
+                self.exceeded.set(Some(e));
+                Err(e)
+            }
+            None => Ok(()),
+        }
+    }
+
+    // Reads everything decompressed by the reader, within the budget
+    fn read_to_end(&self, mut reader: impl Read) -> Result<Vec<u8>, ArchiveErr> {
+        let mut content: Vec<u8> = Vec::new();
+        let mut buffer = [0u8; 64 * 1024];
+        loop {
+            let count = reader.read(&mut buffer).map_err(|_| ArchiveErr::Corrupt)?;
+            if count == 0 {
+                return Ok(content);
+            }
+            self.take(count as u64)?;
+            content.extend_from_slice(&buffer[..count]);
+        }
+    }
+
+    fn is_exceeded(&self) -> bool {
+        self.exceeded.get().is_some()
+    }
+}
+
+pub struct ArchiveClient {
+    path: PathBuf,
+    options: ArchiveOptions,
+}
+
+impl ArchiveClient {
+    pub fn from(path: &Path) -> Result<ArchiveClient, ArchiveErr> {
+        ArchiveClient::with_options(path, ArchiveOptions::default())
+    }
+
+    pub fn with_options(path: &Path, options: ArchiveOptions) -> Result<ArchiveClient, ArchiveErr> {
+        if !path.is_file() {
+            return Err(ArchiveErr::NotFound);
+        }
+
+        Ok(ArchiveClient {
+            path: path.to_path_buf(),
+            options,
+        })
+    }
+
+    // Passes each member, or the error it could not be read with, to `visit`,
+    // until it returns false. A corrupt nested archive or member is skipped,
+    // but once the budget is exceeded the walk is stopped.
+    fn walk(
+        &self,
+        visit: &mut dyn FnMut(Result<Chunk, ArchiveErr>) -> bool,
+    ) -> Result<(), ArchiveErr> {
+        let content = fs::read(&self.path).map_err(|_| ArchiveErr::ReadFailed)?;
+        if Format::detect(&content).is_none() {
+            return Err(ArchiveErr::UnknownFormat);
+        }
+
+        let budget = Budget {
+            used: Cell::new(0),
+            max_total_bytes: self.options.max_total_bytes,
+            max_ratio_bytes: (content.len() as u64).saturating_mul(self.options.max_ratio),
+            exceeded: Cell::new(None),
+        };
+        let mut walker = Walker {
+            budget: &budget,
+            max_depth: self.options.max_depth,
+            visit,
+            stopped: false,
+        };
+        walker.member(self.path.to_string_lossy().into_owned(), content, 0)
+    }
+}
+
+struct Walker<'a> {
+    budget: &'a Budget,
+    max_depth: usize,
+    visit: &'a mut dyn FnMut(Result<Chunk, ArchiveErr>) -> bool,
+    stopped: bool, // once the caller has stopped reading
+}
+
+impl Walker<'_> {
+    fn send(&mut self, chunk: Result<Chunk, ArchiveErr>) {
+        if !(self.visit)(chunk) {
+            self.stopped = true;
+        }
+    }
+
+    fn file(&mut self, path: String, content: Vec<u8>) {
+        if !is_binary(&content) {
+            let mut provenance = Provenance::new(SourceKind::Archive);
+            provenance.path = Some(path);
+            self.send(Ok(Chunk {
+                content,
+                provenance,
+            }));
+        }
+    }
+
+    // Opens the content if it is an archive (or compressed), or scans it
+    fn member(&mut self, path: String, content: Vec<u8>, depth: usize) -> Result<(), ArchiveErr> {
+        let Some(format) = Format::detect(&content) else {
+            self.file(path, content);
+            return Ok(());
+        };
+
+        let decompressed = format
+            .decoder(&content)
+            .map(|decoder| self.budget.read_to_end(decoder));
+        let result = match decompressed {
+            // the decompressed stream is the same file, so is no deeper
+            Some(Ok(decompressed)) => self.member(path, decompressed, depth),
+            // e.g. a text file starting with "BZh", which only looked compressed
+            Some(Err(ArchiveErr::Corrupt)) if !is_binary(&content) => {
+                self.file(path, content);
+                return Ok(());
+            }
+            Some(Err(e)) => Err(e),
+            None if depth > self.max_depth => return Ok(()),
+            None if format == Format::Zip => self.zip(&path, content, depth),
+            None => self.tar(&path, &content, depth),
+        };
+
+        match result {
+            Err(e) if !self.budget.is_exceeded() && depth > 0 => {
+                self.send(Err(e));
+                Ok(())
+            }
+            _ => result,
+        }
+    }
+
+    fn zip(&mut self, path: &str, content: Vec<u8>, depth: usize) -> Result<(), ArchiveErr> {
+        let mut archive =
+            zip::ZipArchive::new(Cursor::new(content)).map_err(|_| ArchiveErr::Corrupt)?;
+
+        for index in 0..archive.len() {
+            if self.stopped {
+                break;
+            }
+
+            // a member which cannot be read is reported, and the others scanned
+            let member = match archive.by_index(index) {
+                Ok(file) if file.is_dir() => continue,
+                Ok(file) => {
+                    let member_path = format!("{}{}{}", path, SEPARATOR, file.name());
+                    self.budget
+                        .read_to_end(file)
+                        .map(|member| (member_path, member))
+                }
+                Err(ZipError::UnsupportedArchive(_)) => Err(ArchiveErr::Unsupported),
+                Err(_) => Err(ArchiveErr::Corrupt),
+            };
+            match member {
+                Ok((member_path, member)) => self.member(member_path, member, depth + 1)?,
+                Err(e) if !self.budget.is_exceeded() => self.send(Err(e)),
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn tar(&mut self, path: &str, content: &[u8], depth: usize) -> Result<(), ArchiveErr> {
+        let mut archive = tar::Archive::new(content);
+        let entries = archive.entries().map_err(|_| ArchiveErr::Corrupt)?;
+
+        for entry in entries {
+            if self.stopped {
+                break;
+            }
+            let mut entry = entry.map_err(|_| ArchiveErr::Corrupt)?;
+            if !entry.header().entry_type().is_file() {
+                continue;
+            }
+            let name = entry.path().map_err(|_| ArchiveErr::Corrupt)?;
+            let member_path = format!("{}{}{}", path, SEPARATOR, name.to_string_lossy());
+
+            // not decompressed, so only bounded by the size of the archive
+            let mut member: Vec<u8> = Vec::new();
+            entry
+                .read_to_end(&mut member)
+                .map_err(|_| ArchiveErr::Corrupt)?;
+            self.member(member_path, member, depth + 1)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Source for ArchiveClient {
+    type Error = ArchiveErr;
+
+    fn kind(&self) -> SourceKind {
+        SourceKind::Archive
+    }
+
+    fn chunks<'a>(&'a mut self) -> Box<dyn Iterator<Item = Result<Chunk, ArchiveErr>> + 'a> {
+        let (sender, receiver) = mpsc::sync_channel::<Result<Chunk, ArchiveErr>>(CHUNKS_AHEAD);
+        let client = ArchiveClient {
+            path: self.path.clone(),
+            options: self.options.clone(),
+        };
+
+        thread::spawn(move || {
+            if let Err(e) = client.walk(&mut |chunk| sender.send(chunk).is_ok()) {
+                let _ = sender.send(Err(e));
+            }
+        });
+
+        Box::new(receiver.into_iter())
+    }
+}
@@ -0,0 +1,248 @@
+use crate::scanner::detector::Backend;
+use crate::scanner::source::archive::client::{ArchiveClient, ArchiveErr, ArchiveOptions};
+use crate::scanner::source::traits::{Source, SourceKind};
+use bzip2::write::BzEncoder;
+use flate2::write::GzEncoder;
+use std::fs;
+use std::io::{Cursor, Write};
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+use xz2::write::XzEncoder;
+use zip::write::SimpleFileOptions;
+use zip::CompressionMethod;
+
+const AWS_API_KEY: &str = "AKIAXR2OBLUTM8DTZV7F";
+
+fn key_file() -> Vec<u8> {
+    format!("aws_access_key_id = {}\n", AWS_API_KEY).into_bytes()
+}
+
+fn zip(files: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    for (name, content) in files {
+        writer
+            .start_file(*name, SimpleFileOptions::default())
+            .unwrap();
+        writer.write_all(content).unwrap();
+    }
+    writer.finish().unwrap().into_inner()
+}
+
+fn tar(files: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut builder = tar::Builder::new(Vec::new());
+    for (name, content) in files {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(content.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder.append_data(&mut header, name, *content).unwrap();
+    }
+    builder.into_inner().unwrap()
+}
+
+fn gzip(content: &[u8]) -> Vec<u8> {
+    let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(content).unwrap();
+    encoder.finish().unwrap()
+}
+
+fn bzip2(content: &[u8]) -> Vec<u8> {
+    let mut encoder = BzEncoder::new(Vec::new(), bzip2::Compression::default());
+    encoder.write_all(content).unwrap();
+    encoder.finish().unwrap()
+}
+
+fn xz(content: &[u8]) -> Vec<u8> {
+    let mut encoder = XzEncoder::new(Vec::new(), 6);
+    encoder.write_all(content).unwrap();
+    encoder.finish().unwrap()
+}
+
+fn write_archive(dir: &TempDir, name: &str, content: &[u8]) -> PathBuf {
+    let path = dir.path().join(name);
+    fs::write(&path, content).unwrap();
+    path
+}
+
+// Paths of the findings, and the errors met along the way
+fn scan(path: &Path, options: ArchiveOptions) -> (Vec<String>, Vec<ArchiveErr>) {
+    let mut source = ArchiveClient::with_options(path, options).unwrap();
+    let detectors = Backend::default().detectors();
+
+    let mut paths: Vec<String> = Vec::new();
+    let mut errors: Vec<ArchiveErr> = Vec::new();
+    for result in source.scan(&detectors) {
+        match result {
+            Ok(finding) => {
+                assert_eq!(finding.provenance.kind, SourceKind::Archive);
+                paths.push(finding.provenance.path.unwrap());
+            }
+            Err(e) => errors.push(e),
+        }
+    }
+
+    (paths, errors)
+}
+
+#[test]
+fn tp_nested_archives() {
+    let inner = zip(&[
+        ("config/", b""),
+        ("config/key.pem", &key_file()),
+        ("config/clean", b"nothing here\n"),
+    ]);
+    let outer = gzip(&tar(&[
+        ("inner.zip", &inner),
+        ("README", b"nothing here either\n"),
+        ("credentials", &key_file()),
+    ]));
+    let dir = TempDir::new().unwrap();
+    let path = write_archive(&dir, "outer.tar.gz", &outer);
+
+    let (paths, errors) = scan(&path, ArchiveOptions::default());
+    let root = path.to_string_lossy();
+    assert_eq!(
+        paths,
+        vec![
+            format!("{}!/inner.zip!/config/key.pem", root),
+            format!("{}!/credentials", root),
+        ]
+    );
+    assert!(errors.is_empty());
+}
+
+#[test]
+fn tp_compressed_files() {
+    let dir = TempDir::new().unwrap();
+    for (name, content) in [
+        ("key.gz", gzip(&key_file())),
+        ("key.bz2", bzip2(&key_file())),
+        ("key.xz", xz(&key_file())),
+        ("key.tar.bz2", bzip2(&tar(&[("key", &key_file())]))),
+        ("key.tar.xz", xz(&tar(&[("key", &key_file())]))),
+    ] {
+        let path = write_archive(&dir, name, &content);
+        let root = path.to_string_lossy().into_owned();
+        let expected = match name.contains(".tar") {
+            true => format!("{}!/key", root),
+            false => root,
+        };
+
+        assert_eq!(
+            scan(&path, ArchiveOptions::default()),
+            (vec![expected], vec![]),
+            "{}",
+            name
+        );
+    }
+}
+
+#[test]
+fn fp_max_depth() {
+    let nested = zip(&[("deep.zip", &zip(&[("key", &key_file())]))]);
+    let dir = TempDir::new().unwrap();
+    let path = write_archive(&dir, "outer.zip", &zip(&[("nested.zip", &nested)]));
+
+    let options = ArchiveOptions {
+        max_depth: 2,
+        ..Default::default()
+    };
+    assert_eq!(scan(&path, options).0.len(), 1);
+
+    let options = ArchiveOptions {
+        max_depth: 1,
+        ..Default::default()
+    };
+    assert_eq!(scan(&path, options), (vec![], vec![]));
+}
+
+#[test]
+fn fp_zip_bomb() {
+    // a few KiB expanding to 8 MiB
+    let bomb = gzip(&tar(&[("zeros", &vec![b'0'; 8 * 1024 * 1024])]));
+    let dir = TempDir::new().unwrap();
+    let path = write_archive(&dir, "bomb.tar.gz", &bomb);
+
+    assert_eq!(
+        scan(&path, ArchiveOptions::default()),
+        (vec![], vec![ArchiveErr::RatioExceeded])
+    );
+
+    let options = ArchiveOptions {
+        max_total_bytes: 1024 * 1024,
+        max_ratio: u64::MAX,
+        ..Default::default()
+    };
+    assert_eq!(scan(&path, options), (vec![], vec![ArchiveErr::TooLarge]));
+
+    let options = ArchiveOptions {
+        max_ratio: u64::MAX,
+        ..Default::default()
+    };
+    assert_eq!(scan(&path, options), (vec![], vec![]));
+}
+
+#[test]
+fn fp_corrupt_nested_archive() {
+    let outer = zip(&[("broken.zip", b"PK\x03\x04broken"), ("key", &key_file())]);
+    let dir = TempDir::new().unwrap();
+    let path = write_archive(&dir, "outer.zip", &outer);
+
+    let (paths, errors) = scan(&path, ArchiveOptions::default());
+    assert_eq!(paths, vec![format!("{}!/key", path.to_string_lossy())]);
+    assert_eq!(errors, vec![ArchiveErr::Corrupt]);
+}
+
+#[test]
+fn fp_unsupported_zip_member() {
+    // stored, then marked as compressed with bzip2, which is not supported
+    let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    let stored = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+    writer.start_file("key.bz2", stored).unwrap();
+    writer.write_all(&key_file()).unwrap();
+    writer.start_file("key", stored).unwrap();
+    writer.write_all(&key_file()).unwrap();
+    let mut outer = writer.finish().unwrap().into_inner();
+    outer[8] = 12;
+    let central = outer.windows(4).position(|w| w == b"PK\x01\x02").unwrap();
+    outer[central + 10] = 12;
+
+    let dir = TempDir::new().unwrap();
+    let path = write_archive(&dir, "outer.zip", &outer);
+
+    let (paths, errors) = scan(&path, ArchiveOptions::default());
+    assert_eq!(paths, vec![format!("{}!/key", path.to_string_lossy())]);
+    assert_eq!(errors, vec![ArchiveErr::Unsupported]);
+}
+
+#[test]
+fn tp_looks_compressed() {
+    let mut notes = b"BZh is the magic of bzip2\n".to_vec();
+    notes.extend(key_file());
+    let outer = tar(&[("notes.txt", &notes)]);
+    let dir = TempDir::new().unwrap();
+    let path = write_archive(&dir, "notes.tar", &outer);
+
+    assert_eq!(
+        scan(&path, ArchiveOptions::default()),
+        (
+            vec![format!("{}!/notes.txt", path.to_string_lossy())],
+            vec![]
+        )
+    );
+}
+
+#[test]
+fn fp_not_an_archive() {
+    let dir = TempDir::new().unwrap();
+    let path = write_archive(&dir, "plain.txt", &key_file());
+
+    assert_eq!(
+        scan(&path, ArchiveOptions::default()),
+        (vec![], vec![ArchiveErr::UnknownFormat])
+    );
+    assert!(matches!(
+        ArchiveClient::from(&dir.path().join("missing.zip")),
+        Err(ArchiveErr::NotFound)
+    ));
+}
@@ -0,0 +1,267 @@
+use crate::scanner::detector::DetectorSet;
+use crate::scanner::finding::Finding;
+use crate::scanner::source::traits::{Chunk, Provenance, Source, SourceKind};
+use ignore::{DirEntry, WalkBuilder, WalkState};
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::thread;
+
+// Scanning of the files in a directory tree, such as a working directory. The
+// tree is walked in parallel, with each file read and scanned on the thread
+// that found it; findings are then passed to the caller on its own thread.
+//
+// Files are skipped as git would skip them: those matched by a .gitignore (or
+// .git/info/exclude, or the global excludes file), as well as by the custom
+// ignore file, which uses the same syntax. Binary files, detected as git does
+// by a NUL byte near the start, are skipped as well.
+//
+// Directories which cannot be walked and files which cannot be read are
+// reported, without stopping the walk.
+
+pub const IGNORE_FILE: &str = ".rstignore";
+pub const MAX_FILE_SIZE: u64 = 10 * 1024 * 1024;
+const GIT_DIR: &str = ".git";
+const CHUNKS_AHEAD: usize = 16;
+
+// as per git's buffer_is_binary
+const BINARY_CHECK_LEN: usize = 8000;
+
+#[derive(Debug)]
+pub enum FsErr {
+    NotFound,
+    WalkFailed,
+    ReadFailed,
+}
+
+#[derive(Clone, Debug)]
+pub struct FsOptions {
+    pub git_ignore: bool,
+    pub ignore_file: Option<String>, // file name, looked for in every directory
+    pub max_file_size: Option<u64>,  // in bytes, larger files are skipped
+    pub follow_symlinks: bool,
+    pub threads: usize, // 0 to pick from the number of CPUs
+}
+
+impl Default for FsOptions {
+    fn default() -> FsOptions {
+        FsOptions {
+            git_ignore: true,
+            ignore_file: Some(String::from(IGNORE_FILE)),
+            max_file_size: Some(MAX_FILE_SIZE),
+            follow_symlinks: false,
+            threads: 0,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct FileFinding {
+    pub path: PathBuf, // as walked, i.e. within the root directory
+    pub line: usize,
+    pub column: usize,
+    pub finding: Finding,
+}
+
+pub struct FsClient {
+    root: PathBuf,
+    options: FsOptions,
+}
+
+pub fn is_binary(content: &[u8]) -> bool {
+    content[..content.len().min(BINARY_CHECK_LEN)].contains(&0)
+}
+
+// Contents of the entry, if it is a text file
+fn read(entry: &DirEntry, max_file_size: Option<u64>) -> Result<Option<Vec<u8>>, FsErr> {
+    if !entry.file_type().is_some_and(|t| t.is_file()) {
+        return Ok(None);
+    }
+
+    let mut file = File::open(entry.path()).map_err(|_| FsErr::ReadFailed)?;
+    let mut content: Vec<u8> = Vec::new();
+    match max_file_size {
+        // the file may have grown since it was walked
+        Some(max) => file.take(max + 1).read_to_end(&mut content),
+        None => file.read_to_end(&mut content),
+    }
+    .map_err(|_| FsErr::ReadFailed)?;
+
+    let too_large = max_file_size.is_some_and(|max| content.len() as u64 > max);
+    match too_large || is_binary(&content) {
+        true => Ok(None),
+        false => Ok(Some(content)),
+    }
+}
+
+impl FsClient {
+    pub fn from(root: &Path) -> Result<FsClient, FsErr> {
+        FsClient::with_options(root, FsOptions::default())
+    }
+
+    pub fn with_options(root: &Path, options: FsOptions) -> Result<FsClient, FsErr> {
+        if !root.exists() {
+            return Err(FsErr::NotFound);
+        }
+
+        Ok(FsClient {
+            root: root.to_path_buf(),
+            options,
+        })
+    }
+
+    fn walk_builder(&self) -> WalkBuilder {
+        let mut builder = WalkBuilder::new(&self.root);
+        builder
+            // dotfiles (e.g. .env) are where secrets are most often found
+            .hidden(false)
+            .parents(true)
+            .ignore(false)
+            .git_ignore(self.options.git_ignore)
+            .git_global(self.options.git_ignore)
+            .git_exclude(self.options.git_ignore)
+            // a directory need not be a git repository for its .gitignore to apply
+            .require_git(false)
+            .follow_links(self.options.follow_symlinks)
+            .max_filesize(self.options.max_file_size)
+            .threads(self.options.threads)
+            .filter_entry(|entry| entry.file_name() != GIT_DIR);
+
+        if let Some(ignore_file) = &self.options.ignore_file {
+            builder.add_custom_ignore_filename(ignore_file);
+        }
+
+        builder
+    }
+
+    fn scan_file(
+        &self,
+        entry: &DirEntry,
+        detectors: &DetectorSet,
+    ) -> Result<Vec<FileFinding>, FsErr> {
+        let content = match read(entry, self.options.max_file_size)? {
+            Some(content) => content,
+            None => return Ok(Vec::new()),
+        };
+
+        Ok(detectors
+            .detect(&content)
+            .map(|detection| FileFinding {
+                path: entry.path().to_path_buf(),
+                line: detection.line,
+                column: detection.column,
+                finding: detection.finding,
+            })
+            .collect())
+    }
+
+    // Walks the directory tree, running the detectors over each file, until
+    // `process` returns false. Directories which cannot be walked and files
+    // which cannot be read do not stop the walk; the first of their errors is
+    // returned once it is done.
+    pub fn scan<F>(&self, detectors: &DetectorSet, mut process: F) -> Result<(), FsErr>
+    where
+        F: FnMut(&FileFinding) -> bool,
+    {
+        let walker = self.walk_builder().build_parallel();
+        let stop = &AtomicBool::new(false);
+        let (sender, receiver) = mpsc::channel::<Result<Vec<FileFinding>, FsErr>>();
+        let mut error: Option<FsErr> = None;
+
+        thread::scope(|scope| {
+            scope.spawn(move || {
+                walker.run(|| {
+                    let sender = sender.clone();
+                    Box::new(move |entry| {
+                        if stop.load(Ordering::Relaxed) {
+                            return WalkState::Quit;
+                        }
+                        let findings = match entry {
+                            Ok(entry) => self.scan_file(&entry, detectors),
+                            Err(_) => Err(FsErr::WalkFailed),
+                        };
+                        if findings.as_ref().is_ok_and(Vec::is_empty) {
+                            return WalkState::Continue;
+                        }
+                        match sender.send(findings) {
+                            Ok(_) => WalkState::Continue,
+                            Err(_) => WalkState::Quit,
+                        }
+                    })
+                });
+                drop(sender);
+            });
+
+            for findings in receiver.iter() {
+                match findings {
+                    Ok(findings) => {
+                        if !findings.iter().all(&mut process) {
+                            stop.store(true, Ordering::Relaxed);
+                            break;
+                        }
+                    }
+                    Err(err) => {
+                        error.get_or_insert(err);
+                    }
+                }
+            }
+        });
+
+        match error {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Source for FsClient {
+    type Error = FsErr;
+
+    fn kind(&self) -> SourceKind {
+        SourceKind::Filesystem
+    }
+
+    // Files are read in parallel, a few ahead of the caller, and yielded in the
+    // order they are read
+    fn chunks<'a>(&'a mut self) -> Box<dyn Iterator<Item = Result<Chunk, FsErr>> + 'a> {
+        let walker = self.walk_builder().build_parallel();
+        let max_file_size = self.options.max_file_size;
+        let (sender, receiver) = mpsc::sync_channel::<Result<Chunk, FsErr>>(CHUNKS_AHEAD);
+
+        thread::spawn(move || {
+            walker.run(|| {
+                let sender = sender.clone();
+                Box::new(move |entry| {
+                    let chunk = entry
+                        .map_err(|_| FsErr::WalkFailed)
+                        .and_then(|entry| {
+                            let content = read(&entry, max_file_size)?;
+                            Ok(content.map(|content| {
+                                let mut provenance = Provenance::new(SourceKind::Filesystem);
+                                provenance.path = Some(entry.path().to_string_lossy().into_owned());
+                                Chunk {
+                                    content,
+                                    provenance,
+                                }
+                            }))
+                        })
+                        .transpose();
+
+                    // a file skipped on purpose, e.g. a binary file
+                    let Some(chunk) = chunk else {
+                        return WalkState::Continue;
+                    };
+                    match sender.send(chunk) {
+                        Ok(_) => WalkState::Continue,
+                        // the caller has stopped reading chunks
+                        Err(_) => WalkState::Quit,
+                    }
+                })
+            });
+        });
+
+        Box::new(receiver.into_iter())
+    }
+}
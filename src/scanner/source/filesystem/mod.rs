@@ -0,0 +1,4 @@
+pub mod client;
+
+#[cfg(test)]
+pub mod test;
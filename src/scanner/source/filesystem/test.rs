@@ -0,0 +1,246 @@
+use crate::scanner::detector::Backend;
+use crate::scanner::finding::Finding;
+use crate::scanner::source::filesystem::client::{FileFinding, FsClient, FsErr, FsOptions};
+use std::fs;
+use std::os::unix::fs::symlink;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+const AWS_API_KEY: &str = "AKIAXR2OBLUTM8DTZV7F";
+
+// Creates the files, along with any missing parent directories
+fn init_dir(files: &[(&str, &[u8])]) -> TempDir {
+    let dir = TempDir::new().unwrap();
+    for (path, content) in files {
+        let path = dir.path().join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+    dir
+}
+
+fn scan(root: &Path, options: FsOptions) -> Vec<FileFinding> {
+    let client = FsClient::with_options(root, options).unwrap();
+    let detectors = Backend::default().detectors();
+
+    let mut findings: Vec<FileFinding> = Vec::new();
+    client
+        .scan(&detectors, |finding| {
+            findings.push(finding.clone());
+            true
+        })
+        .unwrap();
+
+    findings.sort_by(|a, b| a.path.cmp(&b.path));
+    findings
+}
+
+// Paths of the findings, relative to the root
+fn scan_paths(root: &Path, options: FsOptions) -> Vec<PathBuf> {
+    scan(root, options)
+        .into_iter()
+        .map(|f| f.path.strip_prefix(root).unwrap().to_path_buf())
+        .collect()
+}
+
+fn key_line(line: &str) -> Vec<u8> {
+    format!("{}{}\n", line, AWS_API_KEY).into_bytes()
+}
+
+#[test]
+fn tp_scan_files() {
+    let dir = init_dir(&[
+        ("config/.env", &key_line("AWS_ACCESS_KEY_ID=")),
+        ("src/main.rs", b"fn main() {}\n"),
+        (
+            "notes.txt",
+            &[b"todo\n".as_slice(), &key_line("key: ")].concat(),
+        ),
+    ]);
+
+    let findings = scan(dir.path(), FsOptions::default());
+    assert_eq!(findings.len(), 2);
+
+    assert_eq!(findings[0].path, dir.path().join("config/.env"));
+    assert_eq!((findings[0].line, findings[0].column), (1, 19));
+    assert_eq!(findings[1].path, dir.path().join("notes.txt"));
+    assert_eq!((findings[1].line, findings[1].column), (2, 6));
+    assert!(matches!(&findings[1].finding, Finding::AwsApiKey(key) if key == AWS_API_KEY));
+}
+
+#[test]
+fn fp_git_ignore() {
+    let dir = init_dir(&[
+        (".gitignore", b"secrets/\n*.log\n"),
+        ("secrets/credentials", &key_line("")),
+        ("debug.log", &key_line("")),
+        ("nested/.gitignore", b"local\n"),
+        ("nested/local", &key_line("")),
+        ("nested/kept", &key_line("")),
+        (".git/config", &key_line("")),
+    ]);
+
+    assert_eq!(
+        scan_paths(dir.path(), FsOptions::default()),
+        vec![PathBuf::from("nested/kept")]
+    );
+
+    let options = FsOptions {
+        git_ignore: false,
+        ..Default::default()
+    };
+    assert_eq!(
+        scan_paths(dir.path(), options),
+        vec![
+            PathBuf::from("debug.log"),
+            PathBuf::from("nested/kept"),
+            PathBuf::from("nested/local"),
+            PathBuf::from("secrets/credentials"),
+        ]
+    );
+}
+
+#[test]
+fn fp_custom_ignore() {
+    let dir = init_dir(&[
+        (".rstignore", b"fixtures/\n"),
+        ("fixtures/key", &key_line("")),
+        (".scanignore", b"kept\n"),
+        ("kept", &key_line("")),
+    ]);
+    assert_eq!(
+        scan_paths(dir.path(), FsOptions::default()),
+        vec![PathBuf::from("kept")]
+    );
+
+    let options = FsOptions {
+        ignore_file: Some(String::from(".scanignore")),
+        ..Default::default()
+    };
+    assert_eq!(
+        scan_paths(dir.path(), options),
+        vec![PathBuf::from("fixtures/key")]
+    );
+}
+
+#[test]
+fn fp_binary_file() {
+    let binary = [b"\x7fELF\x00\x01".as_slice(), &key_line("")].concat();
+    let dir = init_dir(&[("app", &binary), ("app.conf", &key_line(""))]);
+
+    assert_eq!(
+        scan_paths(dir.path(), FsOptions::default()),
+        vec![PathBuf::from("app.conf")]
+    );
+}
+
+#[test]
+fn fp_max_file_size() {
+    let large = [vec![b'#'; 100], key_line("")].concat();
+    let dir = init_dir(&[("large", &large), ("small", &key_line(""))]);
+
+    let options = FsOptions {
+        max_file_size: Some(64),
+        ..Default::default()
+    };
+    assert_eq!(
+        scan_paths(dir.path(), options),
+        vec![PathBuf::from("small")]
+    );
+
+    let options = FsOptions {
+        max_file_size: None,
+        ..Default::default()
+    };
+    assert_eq!(scan_paths(dir.path(), options).len(), 2);
+}
+
+#[test]
+fn tp_symlinks() {
+    let target = init_dir(&[("key", &key_line(""))]);
+    let dir = init_dir(&[]);
+    symlink(target.path(), dir.path().join("linked")).unwrap();
+
+    assert!(scan_paths(dir.path(), FsOptions::default()).is_empty());
+
+    let options = FsOptions {
+        follow_symlinks: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        scan_paths(dir.path(), options),
+        vec![PathBuf::from("linked/key")]
+    );
+}
+
+#[test]
+fn tp_scan_stopped() {
+    let files: Vec<(String, Vec<u8>)> = (0..32)
+        .map(|i| (format!("{}.txt", i), key_line("")))
+        .collect();
+    let files: Vec<(&str, &[u8])> = files
+        .iter()
+        .map(|(path, content)| (path.as_str(), content.as_slice()))
+        .collect();
+    let dir = init_dir(&files);
+
+    let client = FsClient::from(dir.path()).unwrap();
+    let detectors = Backend::default().detectors();
+    let mut count = 0;
+    client
+        .scan(&detectors, |_finding| {
+            count += 1;
+            false
+        })
+        .unwrap();
+
+    assert_eq!(count, 1);
+}
+
+// Scans the directory, following symlinks, returning the findings along with
+// the result of the scan
+fn scan_unreadable(root: &Path) -> (usize, Result<(), FsErr>) {
+    let options = FsOptions {
+        follow_symlinks: true,
+        ..Default::default()
+    };
+    let client = FsClient::with_options(root, options).unwrap();
+    let detectors = Backend::default().detectors();
+
+    let mut count = 0;
+    let result = client.scan(&detectors, |_finding| {
+        count += 1;
+        true
+    });
+    (count, result)
+}
+
+#[test]
+fn fp_walk_failed() {
+    let dir = init_dir(&[("key", &key_line(""))]);
+    // a directory which links back to its parent cannot be walked
+    symlink(dir.path(), dir.path().join("loop")).unwrap();
+
+    let (count, result) = scan_unreadable(dir.path());
+    assert_eq!(count, 1);
+    assert!(matches!(result, Err(FsErr::WalkFailed)));
+}
+
+#[test]
+fn fp_read_failed() {
+    let dir = init_dir(&[("key", &key_line(""))]);
+    // a file whose reads fail, even as root: the first page of memory is unmapped
+    symlink("/proc/self/mem", dir.path().join("mem")).unwrap();
+
+    let (count, result) = scan_unreadable(dir.path());
+    assert_eq!(count, 1);
+    assert!(matches!(result, Err(FsErr::ReadFailed)));
+}
+
+#[test]
+fn fp_root_not_found() {
+    let dir = TempDir::new().unwrap();
+    let result = FsClient::from(&dir.path().join("missing"));
+
+    assert!(matches!(result, Err(FsErr::NotFound)));
+}
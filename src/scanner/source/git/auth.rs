@@ -0,0 +1,114 @@
+use git2::{Cred, CredentialType, Error, ErrorClass, ErrorCode, FetchOptions, RemoteCallbacks};
+use std::path::PathBuf;
+
+// Authentication for cloning and fetching private repositories. libgit2 asks
+// for credentials again whenever they are rejected, so the number of attempts
+// is capped, after which the operation fails with an authentication error.
+
+const MAX_ATTEMPTS: usize = 3;
+
+// Username sent along with an HTTPS token when the URL has none, as accepted
+// by GitHub
+pub const TOKEN_USERNAME: &str = "x-access-token";
+
+// Username sent over SSH when neither the URL nor the credentials have one
+pub const SSH_USERNAME: &str = "git";
+
+#[derive(Default, Clone)]
+pub enum GitAuth {
+    #[default]
+    None,
+    SshKey {
+        username: String,
+        private_key: PathBuf,
+        public_key: Option<PathBuf>,
+        passphrase: Option<String>,
+    },
+    SshAgent {
+        username: String,
+    },
+    Token {
+        token: String,
+    },
+    UserPass {
+        username: String,
+        password: String,
+    },
+}
+
+fn auth_error(message: &str) -> Error {
+    Error::new(ErrorCode::Auth, ErrorClass::Callback, message)
+}
+
+impl GitAuth {
+    // Credentials for the remote, given the username in its URL (if any) and
+    // the kinds of credentials it accepts
+    pub fn credentials(
+        &self,
+        username_from_url: Option<&str>,
+        allowed: CredentialType,
+    ) -> Result<Cred, Error> {
+        let ssh = allowed.contains(CredentialType::SSH_KEY);
+        let plaintext = allowed.contains(CredentialType::USER_PASS_PLAINTEXT);
+
+        // libgit2 asks for the username alone first when an SSH URL has none
+        if allowed.contains(CredentialType::USERNAME) {
+            let username = match self {
+                GitAuth::SshKey { username, .. } | GitAuth::SshAgent { username } => username,
+                _ => SSH_USERNAME,
+            };
+            return Cred::username(username_from_url.unwrap_or(username));
+        }
+
+        match self {
+            GitAuth::SshKey {
+                username,
+                private_key,
+                public_key,
+                passphrase,
+            } if ssh => Cred::ssh_key(
+                username_from_url.unwrap_or(username),
+                public_key.as_deref(),
+                private_key,
+                passphrase.as_deref(),
+            ),
+            GitAuth::SshAgent { username } if ssh => {
+                Cred::ssh_key_from_agent(username_from_url.unwrap_or(username))
+            }
+            GitAuth::Token { token } if plaintext => {
+                Cred::userpass_plaintext(username_from_url.unwrap_or(TOKEN_USERNAME), token)
+            }
+            GitAuth::UserPass { username, password } if plaintext => {
+                Cred::userpass_plaintext(username, password)
+            }
+            GitAuth::None => Err(auth_error("no credentials configured")),
+            _ => Err(auth_error("credentials not accepted by the remote")),
+        }
+    }
+
+    pub fn remote_callbacks(&self) -> RemoteCallbacks<'_> {
+        let mut callbacks = RemoteCallbacks::new();
+        let mut attempts = 0;
+
+        callbacks.credentials(move |_url, username_from_url, allowed| {
+            attempts += 1;
+            match attempts > MAX_ATTEMPTS {
+                true => Err(auth_error("credentials rejected by the remote")),
+                false => self.credentials(username_from_url, allowed),
+            }
+        });
+
+        callbacks
+    }
+
+    pub fn fetch_options(&self) -> FetchOptions<'_> {
+        let mut options = FetchOptions::new();
+        options.remote_callbacks(self.remote_callbacks());
+        options
+    }
+}
+
+// Whether the error was caused by missing or rejected credentials
+pub fn is_auth_error(error: &Error) -> bool {
+    error.code() == ErrorCode::Auth
+}
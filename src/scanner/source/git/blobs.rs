@@ -0,0 +1,167 @@
+use crate::scanner::detector::DetectorSet;
+use crate::scanner::finding::Finding;
+use crate::scanner::source::git::client::GitErr;
+use git2::{ObjectType, Oid, Repository, Tree};
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+// Scanning of every unique blob reachable from a set of commits. A file that is
+// unchanged across many commits is the same blob in each of them, so it is only
+// read and scanned once.
+//
+// Findings are then mapped back to every commit and path their blob appears at.
+// Trees are shared between commits in the same way as blobs, so each tree is
+// only visited once in either pass, with the results memoised by tree id.
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct BlobOccurrence {
+    pub commit: String,
+    pub path: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct BlobFinding {
+    pub blob: String,
+    pub line: usize,
+    pub column: usize,
+    pub finding: Finding,
+    pub occurrences: Vec<BlobOccurrence>,
+}
+
+// Paths of blobs within a tree, relative to the tree
+type TreeBlobs = Rc<Vec<(String, Oid)>>;
+
+fn find_tree(repo: &Repository, id: Oid) -> Result<Tree<'_>, GitErr> {
+    repo.find_tree(id).map_err(|_| GitErr::ObjectFailed)
+}
+
+// Adds the blobs of the tree, and of its subtrees not visited yet, in the order
+// they are first found
+fn collect_blobs(
+    repo: &Repository,
+    tree: &Tree,
+    seen_trees: &mut HashSet<Oid>,
+    blobs: &mut Vec<Oid>,
+    seen_blobs: &mut HashSet<Oid>,
+) -> Result<(), GitErr> {
+    for entry in tree.iter() {
+        match entry.kind() {
+            Some(ObjectType::Tree) if seen_trees.insert(entry.id()) => {
+                let subtree = find_tree(repo, entry.id())?;
+                collect_blobs(repo, &subtree, seen_trees, blobs, seen_blobs)?;
+            }
+            Some(ObjectType::Blob) if seen_blobs.insert(entry.id()) => blobs.push(entry.id()),
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+// Unique blobs reachable from the commits
+pub fn unique_blobs(repo: &Repository, commits: &[Oid]) -> Result<Vec<Oid>, GitErr> {
+    let mut seen_trees: HashSet<Oid> = HashSet::new();
+    let mut seen_blobs: HashSet<Oid> = HashSet::new();
+    let mut blobs: Vec<Oid> = Vec::new();
+
+    for id in commits {
+        let commit = repo.find_commit(*id).map_err(|_| GitErr::ObjectFailed)?;
+        if seen_trees.insert(commit.tree_id()) {
+            let tree = find_tree(repo, commit.tree_id())?;
+            collect_blobs(repo, &tree, &mut seen_trees, &mut blobs, &mut seen_blobs)?;
+        }
+    }
+
+    Ok(blobs)
+}
+
+// Paths of the target blobs within the tree
+fn target_blobs(
+    repo: &Repository,
+    tree_id: Oid,
+    targets: &HashSet<Oid>,
+    memo: &mut HashMap<Oid, TreeBlobs>,
+) -> Result<TreeBlobs, GitErr> {
+    if let Some(found) = memo.get(&tree_id) {
+        return Ok(found.clone());
+    }
+
+    let tree = find_tree(repo, tree_id)?;
+    let mut found: Vec<(String, Oid)> = Vec::new();
+    for entry in tree.iter() {
+        let name = String::from_utf8_lossy(entry.name_bytes());
+        match entry.kind() {
+            Some(ObjectType::Tree) => {
+                for (path, blob) in target_blobs(repo, entry.id(), targets, memo)?.iter() {
+                    found.push((format!("{}/{}", name, path), *blob));
+                }
+            }
+            Some(ObjectType::Blob) if targets.contains(&entry.id()) => {
+                found.push((name.into_owned(), entry.id()))
+            }
+            _ => {}
+        }
+    }
+
+    let found = Rc::new(found);
+    memo.insert(tree_id, found.clone());
+    Ok(found)
+}
+
+// Every commit and path each of the target blobs appears at
+fn occurrences(
+    repo: &Repository,
+    commits: &[Oid],
+    targets: &HashSet<Oid>,
+) -> Result<HashMap<Oid, Vec<BlobOccurrence>>, GitErr> {
+    let mut memo: HashMap<Oid, TreeBlobs> = HashMap::new();
+    let mut occurrences: HashMap<Oid, Vec<BlobOccurrence>> = HashMap::new();
+
+    for id in commits {
+        let commit = repo.find_commit(*id).map_err(|_| GitErr::ObjectFailed)?;
+        for (path, blob) in target_blobs(repo, commit.tree_id(), targets, &mut memo)?.iter() {
+            occurrences.entry(*blob).or_default().push(BlobOccurrence {
+                commit: id.to_string(),
+                path: path.clone(),
+            });
+        }
+    }
+
+    Ok(occurrences)
+}
+
+// Runs the detectors over each unique blob reachable from the commits
+pub fn scan_blobs(
+    repo: &Repository,
+    commits: &[Oid],
+    detectors: &DetectorSet,
+) -> Result<Vec<BlobFinding>, GitErr> {
+    let mut findings: Vec<(Oid, BlobFinding)> = Vec::new();
+
+    for id in unique_blobs(repo, commits)? {
+        let blob = repo.find_blob(id).map_err(|_| GitErr::ObjectFailed)?;
+        for detection in detectors.detect(blob.content()) {
+            findings.push((
+                id,
+                BlobFinding {
+                    blob: id.to_string(),
+                    line: detection.line,
+                    column: detection.column,
+                    finding: detection.finding,
+                    occurrences: Vec::new(),
+                },
+            ));
+        }
+    }
+
+    let targets: HashSet<Oid> = findings.iter().map(|(id, _)| *id).collect();
+    let occurrences = occurrences(repo, commits, &targets)?;
+
+    Ok(findings
+        .into_iter()
+        .map(|(id, mut finding)| {
+            finding.occurrences = occurrences.get(&id).cloned().unwrap_or_default();
+            finding
+        })
+        .collect())
+}
@@ -1,5 +1,16 @@
-use std::{fs};
-use git2::{Commit, Error, ErrorClass, ErrorCode, Repository, Sort};
+use crate::scanner::detector::DetectorSet;
+use crate::scanner::source::git::auth::GitAuth;
+use crate::scanner::source::git::blobs::{self, BlobFinding};
+use crate::scanner::source::git::cache::{self, Mirror};
+use crate::scanner::source::git::diff::{self, CommitFinding};
+use crate::scanner::source::git::revisions::{GitRef, Revisions};
+use crate::scanner::source::git::state::{RefScan, ScanState};
+use crate::scanner::source::git::url;
+use crate::scanner::source::traits::{Chunk, Source, SourceKind};
+use git2::{Commit, Error, ErrorClass, ErrorCode, Oid, Repository, Revwalk};
+use std::collections::{HashSet, VecDeque};
+use std::iter;
+use std::path::{Path, PathBuf};
 use tempfile::TempDir;
 
 pub const GITHUB_ROOT_HTTPS: &str = "https://github.com/";
@@ -7,7 +18,7 @@ pub const GITHUB_ROOT_SSH: &str = "git@github.com:";
 pub const GITHUB_POSTFIX: &str = ".git";
 pub const GITHUB_ROOT_FILESYSTEM: &str = "file://";
 
-#[derive(Default, Clone)]
+#[derive(Default, Clone, Debug, PartialEq)]
 pub enum GitUrlKind {
     #[default]
     HTTPS,
@@ -26,65 +37,97 @@ pub enum GitErr {
     TempDirFailed,
     InvalidKind,
     CloneFailed,
+    DiffFailed,
+    AuthFailed,
+    CacheFailed,
+    FetchFailed,
+    DateInvalid,
+    ObjectFailed,
+    StateFailed,
 }
 
 pub trait GitPath {
     fn get_path(&self) -> Result<(GitUrlKind, String), GitErr>;
 }
 
+#[derive(Default, Clone)]
+pub struct GitOptions {
+    pub auth: GitAuth,
+    // keep mirrors of remote repositories here between runs, rather than
+    // cloning them into a temporary directory every time
+    pub cache_dir: Option<PathBuf>,
+    pub revisions: Revisions,
+}
+
 pub struct GitClient<'a> {
     url: &'a dyn GitPath,
     repo: Option<Repository>,
+    options: GitOptions,
+    mirror: Option<Mirror>,
+    temp_dir: Option<TempDir>,
 }
 
 impl GitClient<'_> {
     pub fn from(git_url: &dyn GitPath) -> Result<GitClient, GitErr> {
+        GitClient::with_options(git_url, GitOptions::default())
+    }
+
+    pub fn with_options(
+        git_url: &dyn GitPath,
+        options: GitOptions,
+    ) -> Result<GitClient<'_>, GitErr> {
         let mut client = GitClient {
             url: git_url,
             repo: None,
+            options,
+            mirror: None,
+            temp_dir: None,
         };
-        client.repo = Some(client.clone_or_open()?.0);
+        client.clone_or_open()?;
 
         Ok(client)
     }
 
-    fn get_target_path(&self) -> Result<(String, bool), GitErr> {
-        // TODO: Support user specified targets
-        match TempDir::new() {
-            Ok(the_dir) => match the_dir.path().to_str() {
-                None => Err(GitErr::TempDirFailed),
-                Some(path) => Ok((String::from(path), true)),
-            },
-            Err(_) => Err(GitErr::TempDirFailed),
+    fn repo(&self) -> Option<&Repository> {
+        match &self.mirror {
+            Some(mirror) => Some(&mirror.repo),
+            None => self.repo.as_ref(),
         }
     }
 
-    fn clone_or_open(&self) -> Result<(Repository, bool), GitErr> {
-
+    // Clones remote repositories into the cache directory if there is one, or
+    // a temporary directory (deleted along with the client) otherwise
+    fn clone_or_open(&mut self) -> Result<(), GitErr> {
         let (kind, path) = self.url.get_path()?;
 
         match kind {
-            GitUrlKind::HTTPS |GitUrlKind::HTTP | GitUrlKind::SSH => {
-                let target = self.get_target_path()?;
-                let repo = Repository::clone(path.as_str(), target.0);
-                if repo.is_err() { return Err(GitErr::CloneFailed) }
-                Ok((repo.unwrap(), target.1))
+            GitUrlKind::HTTPS | GitUrlKind::HTTP | GitUrlKind::SSH => {
+                if let Some(cache_dir) = &self.options.cache_dir {
+                    self.mirror = Some(cache::open(cache_dir, &path, &self.options.auth)?);
+                    return Ok(());
+                }
+
+                // cloned as a mirror, so that every branch & tag can be walked
+                let target = TempDir::new().map_err(|_| GitErr::TempDirFailed)?;
+                self.repo = Some(cache::clone_mirror(&path, target.path(), &self.options.auth)?);
+                self.temp_dir = Some(target);
+                Ok(())
             }
             GitUrlKind::FILESYSTEM => {
-                let repo = Repository::open(path.as_str());
-                if repo.is_err() { return Err(GitErr::CloneFailed) }
-                Ok((repo.unwrap(), false))
+                let repo = Repository::open(path.as_str()).map_err(|_| GitErr::CloneFailed)?;
+                self.repo = Some(repo);
+                Ok(())
             }
         }
     }
 
-    pub fn walk<F>(&self, process: F) -> Result<(), Error>
+    pub fn walk<F>(&self, mut process: F) -> Result<(), Error>
     where
-        F: Fn(&Repository, &Commit) -> bool,
+        F: FnMut(&Repository, &Commit) -> bool,
     {
-        let repo_result = match self.clone_or_open() {
-            Ok((the_repo, is_temp_dir)) => (the_repo, is_temp_dir),
-            Err(_) => {
+        let repo = match self.repo() {
+            Some(the_repo) => the_repo,
+            None => {
                 return Err(Error::new(
                     ErrorCode::Directory,
                     ErrorClass::Filesystem,
@@ -93,34 +136,258 @@ impl GitClient<'_> {
             }
         };
 
-        let repo = repo_result.0;
-        let is_temp_dir = repo_result.1;
-
         let mut walker = repo.revwalk()?;
-        walker.push_head()?;
-        walker.set_sorting(Sort::NONE)?;
+        self.options.revisions.push(repo, &mut walker)?;
+        self.walk_commits(repo, walker, &mut process)?;
+
+        Ok(())
+    }
 
+    // Runs `process` over the walked commits within the selected dates, and
+    // returns whether every commit was processed
+    fn walk_commits<F>(
+        &self,
+        repo: &Repository,
+        walker: Revwalk,
+        process: &mut F,
+    ) -> Result<bool, Error>
+    where
+        F: FnMut(&Repository, &Commit) -> bool,
+    {
         for oid in walker {
-            if process(&repo, &repo.find_commit(oid?)?) == false {
-                break;
+            let commit = repo.find_commit(oid?)?;
+            if !self.options.revisions.includes(&commit) {
+                continue;
+            }
+            if !process(repo, &commit) {
+                return Ok(false);
             }
         }
 
-        // cleanup temp dir
-        if is_temp_dir {
-            // TODO: better error handling
-            let temp_path = repo.path().to_str().unwrap_or("Unknown");
-            let res = fs::remove_dir_all(temp_path);
+        Ok(true)
+    }
+
+    // Walks the history, running the detectors over the lines added by each
+    // commit, until `process` returns false
+    pub fn scan<F>(&self, detectors: &DetectorSet, mut process: F) -> Result<(), GitErr>
+    where
+        F: FnMut(&CommitFinding) -> bool,
+    {
+        let mut result = Ok(());
+
+        self.walk(|repo, commit| match diff::scan_commit(repo, commit, detectors) {
+            Ok(findings) => findings.iter().all(&mut process),
+            Err(e) => {
+                result = Err(e);
+                false
+            }
+        })
+        .map_err(|_| GitErr::UnableToWalk)?;
+
+        result
+    }
+
+    // Scans each unique blob reachable from the walked commits once, rather
+    // than the changes made by each commit, until `process` returns false
+    pub fn scan_blobs<F>(&self, detectors: &DetectorSet, mut process: F) -> Result<(), GitErr>
+    where
+        F: FnMut(&BlobFinding) -> bool,
+    {
+        let mut commits: Vec<Oid> = Vec::new();
+        self.walk(|_repo, commit| {
+            commits.push(commit.id());
+            true
+        })
+        .map_err(|_| GitErr::UnableToWalk)?;
 
-            if res.is_err() {
-                println!("Unable to delete temp dir: {}", temp_path);
-            } else {
-                println!("Deleted temp dir: {}", temp_path);
+        let repo = self.repo().ok_or(GitErr::CloneOrOpenFail)?;
+        for finding in blobs::scan_blobs(repo, &commits, detectors)? {
+            if !process(&finding) {
+                break;
             }
         }
 
         Ok(())
     }
+
+    // Scans only the commits added to each selected ref since the state file
+    // was last saved, as `scan` would. A ref which was never scanned, or whose
+    // last scanned commit is no longer in its history, is scanned in full.
+    // Ranges are always scanned in full, walked apart from the refs so that the
+    // commits hidden from those do not hide any of theirs. The state is only
+    // saved once the walk has finished, so a failed or stopped scan is picked up
+    // again next time, and never when commits were left out by their date, as
+    // they would not be scanned by a later run without the dates.
+    pub fn scan_incremental<F>(
+        &self,
+        detectors: &DetectorSet,
+        state_path: &Path,
+        mut process: F,
+    ) -> Result<Vec<(String, RefScan)>, GitErr>
+    where
+        F: FnMut(&CommitFinding) -> bool,
+    {
+        let repo = self.repo().ok_or(GitErr::CloneOrOpenFail)?;
+        let revisions = &self.options.revisions;
+        let mut state = ScanState::load(state_path)?;
+
+        let refs = revisions.resolve(repo).map_err(|_| GitErr::UnableToWalk)?;
+        let revwalk = || -> Result<Revwalk, GitErr> {
+            let mut walker = repo.revwalk().map_err(|_| GitErr::UnableToWalk)?;
+            revisions
+                .set_sorting(&mut walker)
+                .map_err(|_| GitErr::UnableToWalk)?;
+            Ok(walker)
+        };
+
+        let mut walkers: Vec<Revwalk> = Vec::new();
+        let mut walker = revwalk()?;
+        let mut scans: Vec<(String, RefScan)> = Vec::new();
+        let mut pushed = false;
+        for (name, tip) in &refs {
+            let scan = match state.get(name) {
+                None => RefScan::New,
+                Some(last) if last == *tip => RefScan::Unchanged,
+                Some(last) if repo.graph_descendant_of(*tip, last).unwrap_or(false) => {
+                    // everything reachable from the last scanned commit has
+                    // been scanned already
+                    walker.hide(last).map_err(|_| GitErr::UnableToWalk)?;
+                    RefScan::Incremental
+                }
+                Some(_) => RefScan::Rewritten,
+            };
+            if scan != RefScan::Unchanged {
+                walker.push(*tip).map_err(|_| GitErr::UnableToWalk)?;
+                pushed = true;
+            }
+            scans.push((name.clone(), scan));
+        }
+        if pushed {
+            walkers.push(walker);
+        }
+
+        let ranges: Vec<&String> = revisions
+            .refs
+            .iter()
+            .filter_map(|git_ref| match git_ref {
+                GitRef::Range(range) => Some(range),
+                _ => None,
+            })
+            .collect();
+        if !ranges.is_empty() {
+            let mut walker = revwalk()?;
+            for range in ranges {
+                walker.push_range(range).map_err(|_| GitErr::UnableToWalk)?;
+            }
+            walkers.push(walker);
+        }
+
+        // a commit both in a range and added to a ref is only scanned once
+        let mut scanned: HashSet<Oid> = HashSet::new();
+        for walker in walkers {
+            let mut result = Ok(());
+            let completed = self
+                .walk_commits(repo, walker, &mut |repo, commit| {
+                    if !scanned.insert(commit.id()) {
+                        return true;
+                    }
+                    match diff::scan_commit(repo, commit, detectors) {
+                        Ok(findings) => findings.iter().all(&mut process),
+                        Err(e) => {
+                            result = Err(e);
+                            false
+                        }
+                    }
+                })
+                .map_err(|_| GitErr::UnableToWalk)?;
+            result?;
+
+            if !completed {
+                return Ok(scans);
+            }
+        }
+
+        if revisions.since.is_some() || revisions.until.is_some() {
+            return Ok(scans);
+        }
+        for (name, tip) in refs {
+            state.set(&name, tip);
+        }
+        state.save(state_path)?;
+
+        Ok(scans)
+    }
+}
+
+// The lines added by each walked commit, a chunk per hunk, as scanned by `scan`
+pub struct CommitChunks<'a> {
+    repo: &'a Repository,
+    revisions: &'a Revisions,
+    walker: Option<Revwalk<'a>>,
+    pending: VecDeque<Chunk>,
+}
+
+impl Iterator for CommitChunks<'_> {
+    type Item = Result<Chunk, GitErr>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(chunk) = self.pending.pop_front() {
+                return Some(Ok(chunk));
+            }
+
+            let commit = match self.walker.as_mut()?.next()? {
+                Ok(oid) => self.repo.find_commit(oid),
+                Err(e) => Err(e),
+            };
+            let commit = match commit {
+                Ok(commit) => commit,
+                Err(_) => {
+                    // the walk cannot go on
+                    self.walker = None;
+                    return Some(Err(GitErr::UnableToWalk));
+                }
+            };
+            if !self.revisions.includes(&commit) {
+                continue;
+            }
+
+            match diff::commit_chunks(self.repo, &commit) {
+                Ok(chunks) => self.pending.extend(chunks),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+impl Source for GitClient<'_> {
+    type Error = GitErr;
+
+    fn kind(&self) -> SourceKind {
+        SourceKind::Git
+    }
+
+    fn chunks<'a>(&'a mut self) -> Box<dyn Iterator<Item = Result<Chunk, GitErr>> + 'a> {
+        let repo = match self.repo() {
+            Some(repo) => repo,
+            None => return Box::new(iter::once(Err(GitErr::CloneOrOpenFail))),
+        };
+
+        let mut walker = match repo.revwalk() {
+            Ok(walker) => walker,
+            Err(_) => return Box::new(iter::once(Err(GitErr::UnableToWalk))),
+        };
+        if self.options.revisions.push(repo, &mut walker).is_err() {
+            return Box::new(iter::once(Err(GitErr::UnableToWalk)));
+        }
+
+        Box::new(CommitChunks {
+            repo,
+            revisions: &self.options.revisions,
+            walker: Some(walker),
+            pending: VecDeque::new(),
+        })
+    }
 }
 
 #[derive(Default)]
@@ -133,3 +400,45 @@ impl GitPath for FileSystemPath<'_> {
         Ok((GitUrlKind::FILESYSTEM, String::from(self.path)))
     }
 }
+
+// A git URL of any kind, see `url::parse`
+#[derive(Default, Clone, Debug)]
+pub struct GitUrl {
+    pub url: String,
+}
+
+impl GitPath for GitUrl {
+    fn get_path(&self) -> Result<(GitUrlKind, String), GitErr> {
+        url::parse(self.url.as_str())
+    }
+}
+
+// An https:// or http:// URL, or the GitHub `owner/repo` shorthand
+#[derive(Default, Clone, Debug)]
+pub struct HttpsGitUrl {
+    pub url: String,
+}
+
+impl GitPath for HttpsGitUrl {
+    fn get_path(&self) -> Result<(GitUrlKind, String), GitErr> {
+        match url::parse(self.url.as_str())? {
+            (kind @ (GitUrlKind::HTTPS | GitUrlKind::HTTP), path) => Ok((kind, path)),
+            _ => Err(GitErr::InvalidKind),
+        }
+    }
+}
+
+// An ssh:// URL, or the scp-like syntax e.g. git@github.com:owner/repo.git
+#[derive(Default, Clone, Debug)]
+pub struct SshGitUrl {
+    pub url: String,
+}
+
+impl GitPath for SshGitUrl {
+    fn get_path(&self) -> Result<(GitUrlKind, String), GitErr> {
+        match url::parse(self.url.as_str())? {
+            (GitUrlKind::SSH, path) => Ok((GitUrlKind::SSH, path)),
+            _ => Err(GitErr::InvalidKind),
+        }
+    }
+}
@@ -0,0 +1,176 @@
+use crate::scanner::detector::DetectorSet;
+use crate::scanner::finding::Finding;
+use crate::scanner::source::git::client::GitErr;
+use crate::scanner::source::traits::{Chunk, CommitInfo, Provenance, SourceKind};
+use git2::{Commit, DiffFormat, DiffOptions, Repository, Tree};
+use std::collections::HashSet;
+
+// Scanning of the changes made by each commit. A commit is diffed against each
+// of its parents (or against nothing, for a root commit), and the detectors are
+// run over the lines it added. For a merge, only lines added relative to every
+// parent are scanned, as the rest were scanned in the commits being merged.
+//
+// Consecutive added lines are scanned together, so that a finding may span
+// several lines (e.g. a PEM block).
+
+#[derive(Clone, Debug)]
+pub struct CommitFinding {
+    pub commit: String,
+    pub path: String,
+    pub line: usize, // in the file, as of the commit
+    pub column: usize,
+    pub author: String,
+    pub email: String,
+    pub timestamp: i64, // seconds since the unix epoch
+    pub finding: Finding,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct AddedLine {
+    path: String,
+    line: usize,
+    content: Vec<u8>,
+}
+
+// A run of consecutive lines added to a file
+struct Hunk {
+    path: String,
+    line: usize, // of the first line
+    count: usize,
+    content: Vec<u8>,
+}
+
+fn added_lines(
+    repo: &Repository,
+    parent: Option<&Tree>,
+    tree: &Tree,
+) -> Result<Vec<AddedLine>, GitErr> {
+    let mut options = DiffOptions::new();
+    options.context_lines(0);
+
+    let diff = repo
+        .diff_tree_to_tree(parent, Some(tree), Some(&mut options))
+        .map_err(|_| GitErr::DiffFailed)?;
+
+    let mut lines: Vec<AddedLine> = Vec::new();
+    diff.print(DiffFormat::Patch, |delta, _hunk, line| {
+        if line.origin() == '+' {
+            if let (Some(path), Some(number)) = (delta.new_file().path(), line.new_lineno()) {
+                lines.push(AddedLine {
+                    path: path.to_string_lossy().into_owned(),
+                    line: number as usize,
+                    content: line.content().to_vec(),
+                });
+            }
+        }
+        true
+    })
+    .map_err(|_| GitErr::DiffFailed)?;
+
+    Ok(lines)
+}
+
+fn hunks(lines: Vec<AddedLine>) -> Vec<Hunk> {
+    let mut hunks: Vec<Hunk> = Vec::new();
+
+    for added in lines {
+        match hunks.last_mut() {
+            Some(hunk) if hunk.path == added.path && hunk.line + hunk.count == added.line => {
+                hunk.count += 1;
+                hunk.content.extend(added.content);
+            }
+            _ => hunks.push(Hunk {
+                path: added.path,
+                line: added.line,
+                count: 1,
+                content: added.content,
+            }),
+        }
+    }
+
+    hunks
+}
+
+// Lines added by the commit, relative to every one of its parents
+fn commit_added_lines(repo: &Repository, commit: &Commit) -> Result<Vec<AddedLine>, GitErr> {
+    let tree = commit.tree().map_err(|_| GitErr::DiffFailed)?;
+
+    if commit.parent_count() == 0 {
+        return added_lines(repo, None, &tree);
+    }
+
+    let mut added: Option<Vec<AddedLine>> = None;
+    for parent in commit.parents() {
+        let parent_tree = parent.tree().map_err(|_| GitErr::DiffFailed)?;
+        let lines = added_lines(repo, Some(&parent_tree), &tree)?;
+
+        added = Some(match added {
+            None => lines,
+            Some(previous) => {
+                let lines: HashSet<AddedLine> = lines.into_iter().collect();
+                previous.into_iter().filter(|l| lines.contains(l)).collect()
+            }
+        });
+    }
+
+    Ok(added.unwrap_or_default())
+}
+
+fn commit_info(commit: &Commit) -> CommitInfo {
+    let author = commit.author();
+    CommitInfo {
+        id: commit.id().to_string(),
+        author: String::from(author.name().unwrap_or("Unknown")),
+        email: String::from(author.email().unwrap_or("Unknown")),
+        timestamp: author.when().seconds(),
+    }
+}
+
+// The lines added by the commit, a chunk per hunk
+pub fn commit_chunks(repo: &Repository, commit: &Commit) -> Result<Vec<Chunk>, GitErr> {
+    let info = commit_info(commit);
+
+    let chunks = hunks(commit_added_lines(repo, commit)?)
+        .into_iter()
+        .map(|hunk| Chunk {
+            content: hunk.content,
+            provenance: Provenance {
+                kind: SourceKind::Git,
+                path: Some(hunk.path),
+                commit: Some(info.clone()),
+                layer: None,
+                line_offset: hunk.line - 1,
+            },
+        })
+        .collect();
+
+    Ok(chunks)
+}
+
+// Runs the detectors over the lines added by the commit
+pub fn scan_commit(
+    repo: &Repository,
+    commit: &Commit,
+    detectors: &DetectorSet,
+) -> Result<Vec<CommitFinding>, GitErr> {
+    let info = commit_info(commit);
+
+    let mut findings: Vec<CommitFinding> = Vec::new();
+    for chunk in commit_chunks(repo, commit)? {
+        let path = chunk.provenance.path.clone().unwrap_or_default();
+        for found in chunk.scan(detectors) {
+            findings.push(CommitFinding {
+                commit: info.id.clone(),
+                path: path.clone(),
+                line: found.line,
+                column: found.column,
+                author: info.author.clone(),
+                email: info.email.clone(),
+                timestamp: info.timestamp,
+                finding: found.finding,
+            });
+        }
+    }
+
+    Ok(findings)
+}
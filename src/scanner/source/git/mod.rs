@@ -1,5 +1,12 @@
+pub mod auth;
+pub mod blobs;
+pub mod cache;
 #[allow(dead_code)]
 pub mod client;
+pub mod diff;
+pub mod revisions;
+pub mod state;
+pub mod url;
 
 #[cfg(test)]
 pub mod test;
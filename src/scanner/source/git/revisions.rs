@@ -0,0 +1,196 @@
+use crate::scanner::common::charset;
+use crate::scanner::source::git::client::GitErr;
+use git2::{Commit, Error, Oid, Repository, Revwalk, Sort};
+use winnow::combinator::{opt, preceded, terminated};
+use winnow::token::take_while;
+use winnow::{PResult, Parser};
+
+// Selection of the commits walked: which refs they are reachable from, when
+// they were committed and the order they are walked in.
+
+pub const BRANCHES_GLOB: &str = "refs/heads/*";
+pub const TAGS_GLOB: &str = "refs/tags/*";
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum GitRef {
+    Head,
+    AllBranches,
+    AllTags,
+    Glob(String),  // e.g. refs/heads/release/*
+    Range(String), // e.g. main..feature, the commits of feature not in main
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum GitSort {
+    #[default]
+    None,
+    Topological, // parents after all of their children
+    Time,        // newest commit first
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Revisions {
+    pub refs: Vec<GitRef>,  // HEAD if empty
+    pub since: Option<i64>, // committed at or after, in seconds since the unix epoch
+    pub until: Option<i64>, // committed at or before
+    pub sort: GitSort,
+    pub reverse: bool,
+}
+
+pub const HEAD: &str = "HEAD";
+
+// Expands a glob as git does: "refs/" is prepended if missing, and "/*" is
+// appended if it has no wildcard
+fn expand_glob(glob: &str) -> String {
+    let mut expanded = match glob.starts_with("refs/") {
+        true => String::from(glob),
+        false => format!("refs/{}", glob),
+    };
+    if !glob.contains(['*', '?', '[']) {
+        expanded.push_str("/*");
+    }
+    expanded
+}
+
+impl Revisions {
+    pub fn set_sorting(&self, walker: &mut Revwalk) -> Result<(), Error> {
+        let sort = match self.sort {
+            GitSort::None => Sort::NONE,
+            GitSort::Topological => Sort::TOPOLOGICAL,
+            GitSort::Time => Sort::TIME,
+        };
+        match self.reverse {
+            true => walker.set_sorting(sort | Sort::REVERSE),
+            false => walker.set_sorting(sort),
+        }
+    }
+
+    // Sets up the walker to walk the selected refs in the selected order. A glob
+    // matching no refs is an error, as it is most likely a misspelt ref.
+    pub fn push(&self, repo: &Repository, walker: &mut Revwalk) -> Result<(), Error> {
+        self.set_sorting(walker)?;
+
+        if self.refs.is_empty() {
+            return walker.push_head();
+        }
+
+        for git_ref in &self.refs {
+            match git_ref {
+                GitRef::Head => walker.push_head()?,
+                GitRef::AllBranches => walker.push_glob(BRANCHES_GLOB)?,
+                GitRef::AllTags => walker.push_glob(TAGS_GLOB)?,
+                GitRef::Glob(glob) => {
+                    if repo.references_glob(&expand_glob(glob))?.next().is_none() {
+                        return Err(Error::from_str(&format!("no refs match {}", glob)));
+                    }
+                    walker.push_glob(glob)?
+                }
+                GitRef::Range(range) => walker.push_range(range)?,
+            }
+        }
+
+        Ok(())
+    }
+
+    // Names of the selected refs, along with the commit each points at. A range
+    // is not a ref, so is left out.
+    pub fn resolve(&self, repo: &Repository) -> Result<Vec<(String, Oid)>, Error> {
+        let head = [GitRef::Head];
+        let refs = match self.refs.is_empty() {
+            true => &head[..],
+            false => &self.refs[..],
+        };
+
+        let mut resolved: Vec<(String, Oid)> = Vec::new();
+        for git_ref in refs {
+            let glob = match git_ref {
+                GitRef::Head => {
+                    resolved.push((String::from(HEAD), repo.head()?.peel_to_commit()?.id()));
+                    continue;
+                }
+                GitRef::AllBranches => String::from(BRANCHES_GLOB),
+                GitRef::AllTags => String::from(TAGS_GLOB),
+                GitRef::Glob(glob) => expand_glob(glob),
+                GitRef::Range(_) => continue,
+            };
+
+            for reference in repo.references_glob(&glob)? {
+                let reference = reference?;
+                // tags may point at objects other than commits
+                if let (Some(name), Ok(commit)) = (reference.name(), reference.peel_to_commit()) {
+                    resolved.push((String::from(name), commit.id()));
+                }
+            }
+        }
+
+        Ok(resolved)
+    }
+
+    // Whether the commit was made within the selected dates
+    pub fn includes(&self, commit: &Commit) -> bool {
+        let time = commit.time().seconds();
+        self.since.is_none_or(|since| time >= since) && self.until.is_none_or(|until| time <= until)
+    }
+}
+
+fn number(input: &mut &str) -> PResult<i64> {
+    take_while(1.., charset::ASCII_NUM)
+        .try_map(str::parse::<i64>)
+        .parse_next(input)
+}
+
+// In the Gregorian calendar, where February has a 29th day in leap years
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// Days since the unix epoch of a date in the proleptic Gregorian calendar, see:
+// https://howardhinnant.github.io/date_algorithms.html#days_from_civil
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+// YYYY-MM-DD[THH:MM:SS[Z]], in UTC
+fn date_time(input: &mut &str) -> PResult<i64> {
+    (
+        terminated(number, '-'),
+        terminated(number, '-'),
+        number,
+        opt(preceded(
+            'T',
+            (
+                terminated(number, ':'),
+                terminated(number, ':'),
+                terminated(number, opt('Z')),
+            ),
+        )),
+    )
+        .verify(|(year, month, day, time)| {
+            (1..=12).contains(month)
+                && (1..=days_in_month(*year, *month)).contains(day)
+                && time.is_none_or(|(h, m, s)| h < 24 && m < 60 && s < 60)
+        })
+        .map(|(year, month, day, time)| {
+            let (hours, minutes, seconds) = time.unwrap_or((0, 0, 0));
+            days_from_civil(year, month, day) * 86400 + hours * 3600 + minutes * 60 + seconds
+        })
+        .parse_next(input)
+}
+
+// Parses a date, as a UTC date (and time) or seconds since the unix epoch
+pub fn parse_date(date: &str) -> Result<i64, GitErr> {
+    date_time
+        .parse(date)
+        .or_else(|_| number.parse(date))
+        .map_err(|_| GitErr::DateInvalid)
+}
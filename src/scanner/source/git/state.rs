@@ -0,0 +1,77 @@
+use crate::scanner::source::git::client::GitErr;
+use git2::Oid;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+
+// State of incremental scans: the last commit scanned for each ref. It is saved
+// as a text file with a line per ref, in the same format as git's packed-refs:
+//
+//   <commit id> <ref name>
+//
+// Blank lines, and lines starting with "#", are ignored.
+
+const HEADER: &str = "# last scanned commit of each ref";
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ScanState {
+    refs: BTreeMap<String, Oid>,
+}
+
+// How a ref was scanned, compared to its last scan
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RefScan {
+    New,         // never scanned, so scanned in full
+    Unchanged,   // nothing new to scan
+    Incremental, // only the commits since the last scan were scanned
+    Rewritten,   // the last scanned commit is gone from its history (e.g. a
+                 // force-push) so it was scanned in full
+}
+
+impl ScanState {
+    // Loads the state, which is empty if the file does not exist yet
+    pub fn load(path: &Path) -> Result<ScanState, GitErr> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(ScanState::default()),
+            Err(_) => return Err(GitErr::StateFailed),
+        };
+
+        let mut state = ScanState::default();
+        for line in contents.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (id, name) = line.split_once(' ').ok_or(GitErr::StateFailed)?;
+            let id = Oid::from_str(id).map_err(|_| GitErr::StateFailed)?;
+            state.refs.insert(String::from(name), id);
+        }
+
+        Ok(state)
+    }
+
+    // Saves the state, replacing the file in one step so that an interrupted
+    // save cannot leave a partial state behind
+    pub fn save(&self, path: &Path) -> Result<(), GitErr> {
+        let mut contents = String::from(HEADER);
+        contents.push('\n');
+        for (name, id) in &self.refs {
+            contents.push_str(&format!("{} {}\n", id, name));
+        }
+
+        let mut temp_path = path.as_os_str().to_owned();
+        temp_path.push(".tmp");
+        fs::write(&temp_path, contents).map_err(|_| GitErr::StateFailed)?;
+        fs::rename(&temp_path, path).map_err(|_| GitErr::StateFailed)
+    }
+
+    pub fn get(&self, name: &str) -> Option<Oid> {
+        self.refs.get(name).copied()
+    }
+
+    pub fn set(&mut self, name: &str, id: Oid) {
+        self.refs.insert(String::from(name), id);
+    }
+}
@@ -0,0 +1,108 @@
+use crate::scanner::common::charset;
+use crate::scanner::source::git::client::{GitErr, GitUrlKind, GITHUB_POSTFIX, GITHUB_ROOT_HTTPS};
+use winnow::combinator::{opt, preceded, terminated};
+use winnow::token::take_while;
+use winnow::{PResult, Parser};
+
+// Parser for the URLs git accepts for a remote repository, see:
+// https://git-scm.com/docs/git-clone#_git_urls
+//
+// Supports https://, http://, ssh:// and file:// URLs, the scp-like syntax for
+// SSH (e.g. git@github.com:owner/repo.git) and the GitHub `owner/repo` shorthand.
+
+fn scheme<'a>(input: &mut &'a str) -> PResult<&'a str> {
+    terminated(
+        take_while(1.., (charset::ASCII_ALPHA_NUM, '+', '-', '.')),
+        "://",
+    )
+    .parse_next(input)
+}
+
+fn user<'a>(input: &mut &'a str) -> PResult<&'a str> {
+    terminated(
+        take_while(1.., |c: char| {
+            charset::URL_CHARS.contains(&c) && c != '@' && c != '/'
+        }),
+        '@',
+    )
+    .parse_next(input)
+}
+
+fn host<'a>(input: &mut &'a str) -> PResult<&'a str> {
+    take_while(1.., (charset::ASCII_ALPHA_NUM, '-', '.')).parse_next(input)
+}
+
+fn port<'a>(input: &mut &'a str) -> PResult<&'a str> {
+    preceded(':', take_while(1.., charset::ASCII_NUM)).parse_next(input)
+}
+
+fn path<'a>(input: &mut &'a str) -> PResult<&'a str> {
+    take_while(1.., charset::URL_CHARS).parse_next(input)
+}
+
+// [user@]host[:port]/path, following the scheme
+fn remote(input: &mut &str) -> PResult<()> {
+    (opt(user), host, opt(port), preceded('/', path))
+        .void()
+        .parse_next(input)
+}
+
+// [user@]host:path
+fn scp_like(input: &mut &str) -> PResult<()> {
+    (opt(user), host, ':', path).void().parse_next(input)
+}
+
+// owner/repo, as per GitHub's naming rules
+fn shorthand<'a>(input: &mut &'a str) -> PResult<(&'a str, &'a str)> {
+    (
+        take_while(1.., (charset::ASCII_ALPHA_NUM, '-')),
+        preceded(
+            '/',
+            take_while(1.., (charset::ASCII_ALPHA_NUM, '-', '_', '.')),
+        ),
+    )
+        .verify(|(_, repo): &(&str, &str)| *repo != "." && *repo != "..")
+        .parse_next(input)
+}
+
+// Parses a git URL, returning its kind along with the URL to clone or the path
+// to open. The shorthand is expanded to a GitHub HTTPS URL.
+pub fn parse(url: &str) -> Result<(GitUrlKind, String), GitErr> {
+    let mut rest = url;
+
+    if let Ok(scheme) = scheme(&mut rest) {
+        let kind = match scheme {
+            "https" => GitUrlKind::HTTPS,
+            "http" => GitUrlKind::HTTP,
+            "ssh" => GitUrlKind::SSH,
+            "file" => GitUrlKind::FILESYSTEM,
+            _ => return Err(GitErr::UrlUnknown),
+        };
+
+        return match kind {
+            GitUrlKind::FILESYSTEM => match path.parse(rest) {
+                Ok(path) => Ok((kind, String::from(path))),
+                Err(_) => Err(GitErr::UrlInvalid),
+            },
+            _ => match remote.parse(rest) {
+                Ok(_) => Ok((kind, String::from(url))),
+                Err(_) => Err(GitErr::UrlInvalid),
+            },
+        };
+    }
+
+    if scp_like.parse(url).is_ok() {
+        return Ok((GitUrlKind::SSH, String::from(url)));
+    }
+
+    match shorthand.parse(url) {
+        Ok((owner, repo)) => {
+            let repo = repo.strip_suffix(GITHUB_POSTFIX).unwrap_or(repo);
+            Ok((
+                GitUrlKind::HTTPS,
+                [GITHUB_ROOT_HTTPS, owner, "/", repo, GITHUB_POSTFIX].concat(),
+            ))
+        }
+        Err(_) => Err(GitErr::UrlInvalid),
+    }
+}
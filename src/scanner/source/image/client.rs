@@ -0,0 +1,427 @@
+use crate::scanner::source::filesystem::client::{is_binary, MAX_FILE_SIZE};
+use crate::scanner::source::traits::{Chunk, Provenance, Source, SourceKind};
+use flate2::read::MultiGzDecoder;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::{Component, Path, PathBuf};
+use std::sync::mpsc::{self, SyncSender};
+use std::thread;
+
+// Scanning of container images, as saved by `docker save` or as OCI layout
+// directories (or tarballs of them), without a running daemon. The manifests
+// are followed to the config and layers of every image: each layer is a tar of
+// the files it adds, and every file in every layer is scanned, so files deleted
+// by a later layer (with a whiteout) are still found. The environment
+// variables and history commands of the image config are scanned as well, a
+// line per variable or command.
+//
+// The manifests are read from the image, which may not be trusted: digests and
+// names which would lead outside of the image are rejected.
+
+pub const DOCKER_MANIFEST: &str = "manifest.json";
+pub const OCI_INDEX: &str = "index.json";
+pub const ENV_PATH: &str = "config/Env";
+pub const HISTORY_PATH: &str = "config/History";
+
+const WHITEOUT_PREFIX: &str = ".wh.";
+const GZIP_MAGIC: &[u8] = b"\x1f\x8b";
+const ZSTD_MAGIC: &[u8] = b"\x28\xb5\x2f\xfd";
+const CHUNKS_AHEAD: usize = 16;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ImageErr {
+    NotFound,
+    NotAnImage,
+    ReadFailed,
+    ManifestInvalid,
+    BlobMissing,
+    LayerCorrupt,
+    LayerUnsupported, // e.g. zstd compressed
+    PathInvalid,      // a digest or name leading outside of the image
+}
+
+#[derive(Clone, Debug)]
+pub struct ImageOptions {
+    pub max_file_size: Option<u64>, // in bytes, larger files in layers are skipped
+}
+
+impl Default for ImageOptions {
+    fn default() -> ImageOptions {
+        ImageOptions {
+            max_file_size: Some(MAX_FILE_SIZE),
+        }
+    }
+}
+
+// An entry of the manifest.json written by `docker save`
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct DockerManifest {
+    config: String,
+    #[serde(default)]
+    layers: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct Descriptor {
+    digest: String,
+}
+
+// An OCI image index, or image manifest
+#[derive(Deserialize)]
+struct OciManifest {
+    #[serde(default)]
+    manifests: Vec<Descriptor>,
+    config: Option<Descriptor>,
+    #[serde(default)]
+    layers: Vec<Descriptor>,
+}
+
+#[derive(Deserialize)]
+struct ImageConfig {
+    config: Option<ContainerConfig>,
+    #[serde(default)]
+    history: Vec<History>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ContainerConfig {
+    env: Option<Vec<String>>,
+}
+
+#[derive(Deserialize)]
+struct History {
+    created_by: Option<String>,
+}
+
+// Config and layers of an image, by their names within the layout
+struct Image {
+    config: String,
+    layers: Vec<String>,
+}
+
+// <algorithm>:<hex>, see: https://github.com/opencontainers/image-spec/blob/main/descriptor.md#digests
+fn is_digest(digest: &str) -> bool {
+    digest.split_once(':').is_some_and(|(algorithm, hex)| {
+        !algorithm.is_empty()
+            && !hex.is_empty()
+            && algorithm
+                .bytes()
+                .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit())
+            && hex
+                .bytes()
+                .all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b))
+    })
+}
+
+fn blob_path(digest: &str) -> Result<String, ImageErr> {
+    match is_digest(digest) {
+        true => Ok(format!("blobs/{}", digest.replacen(':', "/", 1))),
+        false => Err(ImageErr::PathInvalid),
+    }
+}
+
+// Whether the name stays within the layout, i.e. is relative and never goes up
+fn is_contained(name: &str) -> bool {
+    Path::new(name)
+        .components()
+        .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+}
+
+// Digest of a blob, from its name within the layout. Layers of older `docker
+// save` tarballs are named by id (e.g. <id>/layer.tar), which is kept as is.
+fn digest(name: &str) -> String {
+    match name.strip_prefix("blobs/").and_then(|n| n.split_once('/')) {
+        Some((algorithm, hex)) => format!("{}:{}", algorithm, hex),
+        None => String::from(name),
+    }
+}
+
+// Where the blobs of the image are kept
+enum Layout {
+    Directory(PathBuf),
+    // offset and size of each file within the tarball
+    Tarball(PathBuf, HashMap<String, (u64, u64)>),
+}
+
+impl Layout {
+    fn open(path: &Path) -> Result<Layout, ImageErr> {
+        if path.is_dir() {
+            return Ok(Layout::Directory(path.to_path_buf()));
+        }
+
+        let file = File::open(path).map_err(|_| ImageErr::NotFound)?;
+        let mut archive = tar::Archive::new(file);
+        let mut files: HashMap<String, (u64, u64)> = HashMap::new();
+        for entry in archive.entries().map_err(|_| ImageErr::NotAnImage)? {
+            let entry = entry.map_err(|_| ImageErr::NotAnImage)?;
+            let name = entry.path().map_err(|_| ImageErr::NotAnImage)?;
+            let name = name.to_string_lossy();
+            let name = name.trim_start_matches("./");
+            files.insert(
+                String::from(name),
+                (entry.raw_file_position(), entry.size()),
+            );
+        }
+
+        Ok(Layout::Tarball(path.to_path_buf(), files))
+    }
+
+    fn exists(&self, name: &str) -> bool {
+        match self {
+            Layout::Directory(dir) => is_contained(name) && dir.join(name).is_file(),
+            Layout::Tarball(_, files) => files.contains_key(name),
+        }
+    }
+
+    fn reader(&self, name: &str) -> Result<Box<dyn Read + Send>, ImageErr> {
+        if !is_contained(name) {
+            return Err(ImageErr::PathInvalid);
+        }
+
+        match self {
+            Layout::Directory(dir) => match File::open(dir.join(name)) {
+                Ok(file) => Ok(Box::new(file)),
+                Err(_) => Err(ImageErr::BlobMissing),
+            },
+            Layout::Tarball(path, files) => {
+                let (offset, size) = files.get(name).ok_or(ImageErr::BlobMissing)?;
+                let mut file = File::open(path).map_err(|_| ImageErr::ReadFailed)?;
+                file.seek(SeekFrom::Start(*offset))
+                    .map_err(|_| ImageErr::ReadFailed)?;
+                Ok(Box::new(file.take(*size)))
+            }
+        }
+    }
+
+    fn json<T: DeserializeOwned>(&self, name: &str) -> Result<T, ImageErr> {
+        serde_json::from_reader(BufReader::new(self.reader(name)?))
+            .map_err(|_| ImageErr::ManifestInvalid)
+    }
+
+    // Every image, as listed by either the docker or OCI manifests
+    fn images(&self) -> Result<Vec<Image>, ImageErr> {
+        if self.exists(DOCKER_MANIFEST) {
+            let manifests: Vec<DockerManifest> = self.json(DOCKER_MANIFEST)?;
+            return Ok(manifests
+                .into_iter()
+                .map(|m| Image {
+                    config: m.config,
+                    layers: m.layers,
+                })
+                .collect());
+        }
+        if !self.exists(OCI_INDEX) {
+            return Err(ImageErr::NotAnImage);
+        }
+
+        let mut images: Vec<Image> = Vec::new();
+        let mut pending: Vec<String> = vec![String::from(OCI_INDEX)];
+        let mut seen: HashSet<String> = HashSet::new();
+        while let Some(name) = pending.pop() {
+            if !seen.insert(name.clone()) {
+                continue;
+            }
+
+            // an index lists manifests (or further indexes, e.g. per platform)
+            let manifest: OciManifest = self.json(&name)?;
+            for m in manifest.manifests.iter().rev() {
+                pending.push(blob_path(&m.digest)?);
+            }
+            if let Some(config) = manifest.config {
+                images.push(Image {
+                    config: blob_path(&config.digest)?,
+                    layers: manifest
+                        .layers
+                        .iter()
+                        .map(|l| blob_path(&l.digest))
+                        .collect::<Result<_, _>>()?,
+                });
+            }
+        }
+
+        Ok(images)
+    }
+}
+
+pub struct ImageClient {
+    path: PathBuf,
+    options: ImageOptions,
+}
+
+impl ImageClient {
+    pub fn from(path: &Path) -> Result<ImageClient, ImageErr> {
+        ImageClient::with_options(path, ImageOptions::default())
+    }
+
+    pub fn with_options(path: &Path, options: ImageOptions) -> Result<ImageClient, ImageErr> {
+        if !path.exists() {
+            return Err(ImageErr::NotFound);
+        }
+
+        Ok(ImageClient {
+            path: path.to_path_buf(),
+            options,
+        })
+    }
+}
+
+struct Walker {
+    layout: Layout,
+    options: ImageOptions,
+    sender: SyncSender<Result<Chunk, ImageErr>>,
+}
+
+impl Walker {
+    // Passes the chunk on, returning false once the caller has stopped reading
+    fn send(&self, chunk: Result<Chunk, ImageErr>) -> bool {
+        self.sender.send(chunk).is_ok()
+    }
+
+    // Whether to carry on. A config or layer which cannot be read is reported
+    // without stopping the scan of the others.
+    fn proceed(&self, result: Result<bool, ImageErr>) -> bool {
+        match result {
+            Ok(more) => more,
+            Err(e) => self.send(Err(e)),
+        }
+    }
+
+    fn walk(&self) -> Result<(), ImageErr> {
+        let mut scanned: HashSet<String> = HashSet::new();
+
+        for image in self.layout.images()? {
+            if scanned.insert(image.config.clone()) && !self.proceed(self.config(&image.config)) {
+                return Ok(());
+            }
+            // layers are often shared between images
+            for layer in image.layers {
+                if scanned.insert(layer.clone()) && !self.proceed(self.layer(&layer)) {
+                    return Ok(());
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn config(&self, name: &str) -> Result<bool, ImageErr> {
+        let config: ImageConfig = self.layout.json(name)?;
+
+        let env = config.config.and_then(|c| c.env).unwrap_or_default();
+        // history entries without a command still count as a line
+        let history: Vec<String> = config
+            .history
+            .into_iter()
+            .map(|h| h.created_by.unwrap_or_default())
+            .collect();
+
+        for (path, lines) in [(ENV_PATH, env), (HISTORY_PATH, history)] {
+            let mut provenance = Provenance::new(SourceKind::Image);
+            provenance.path = Some(String::from(path));
+            provenance.layer = Some(digest(name));
+
+            let mut content = lines.join("\n");
+            content.push('\n');
+            let chunk = Chunk {
+                content: content.into_bytes(),
+                provenance,
+            };
+            if !self.send(Ok(chunk)) {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+
+    fn layer(&self, name: &str) -> Result<bool, ImageErr> {
+        let mut reader = BufReader::new(self.layout.reader(name)?);
+        let magic = reader.fill_buf().map_err(|_| ImageErr::ReadFailed)?;
+        let reader: Box<dyn Read> = match magic {
+            _ if magic.starts_with(GZIP_MAGIC) => Box::new(MultiGzDecoder::new(reader)),
+            _ if magic.starts_with(ZSTD_MAGIC) => return Err(ImageErr::LayerUnsupported),
+            _ => Box::new(reader),
+        };
+
+        let layer = digest(name);
+        let mut archive = tar::Archive::new(reader);
+        for entry in archive.entries().map_err(|_| ImageErr::LayerCorrupt)? {
+            let mut entry = entry.map_err(|_| ImageErr::LayerCorrupt)?;
+            let too_large = self
+                .options
+                .max_file_size
+                .is_some_and(|max| entry.size() > max);
+            if !entry.header().entry_type().is_file() || too_large {
+                continue;
+            }
+
+            let path = entry.path().map_err(|_| ImageErr::LayerCorrupt)?;
+            let path = path.to_string_lossy().trim_start_matches("./").to_owned();
+            let is_whiteout = Path::new(&path)
+                .file_name()
+                .is_some_and(|n| n.to_string_lossy().starts_with(WHITEOUT_PREFIX));
+            if is_whiteout {
+                continue;
+            }
+
+            let mut content: Vec<u8> = Vec::new();
+            entry
+                .read_to_end(&mut content)
+                .map_err(|_| ImageErr::LayerCorrupt)?;
+            if is_binary(&content) {
+                continue;
+            }
+
+            let mut provenance = Provenance::new(SourceKind::Image);
+            provenance.path = Some(path);
+            provenance.layer = Some(layer.clone());
+            if !self.send(Ok(Chunk {
+                content,
+                provenance,
+            })) {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+}
+
+impl Source for ImageClient {
+    type Error = ImageErr;
+
+    fn kind(&self) -> SourceKind {
+        SourceKind::Image
+    }
+
+    // Layers are read on another thread, a few files ahead of the caller
+    fn chunks<'a>(&'a mut self) -> Box<dyn Iterator<Item = Result<Chunk, ImageErr>> + 'a> {
+        let (sender, receiver) = mpsc::sync_channel::<Result<Chunk, ImageErr>>(CHUNKS_AHEAD);
+        let path = self.path.clone();
+        let options = self.options.clone();
+
+        thread::spawn(move || {
+            let walker = match Layout::open(&path) {
+                Ok(layout) => Walker {
+                    layout,
+                    options,
+                    sender,
+                },
+                Err(e) => {
+                    let _ = sender.send(Err(e));
+                    return;
+                }
+            };
+            if let Err(e) = walker.walk() {
+                walker.send(Err(e));
+            }
+        });
+
+        Box::new(receiver.into_iter())
+    }
+}
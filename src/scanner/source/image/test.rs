@@ -0,0 +1,285 @@
+use crate::scanner::detector::Backend;
+use crate::scanner::source::image::client::{ImageClient, ImageErr, ENV_PATH, HISTORY_PATH};
+use crate::scanner::source::traits::{Source, SourceKind};
+use flate2::write::GzEncoder;
+use openssl::sha::sha256;
+use serde_json::json;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use tempfile::TempDir;
+
+const AWS_API_KEY: &str = "AKIAXR2OBLUTM8DTZV7F";
+
+fn tar(files: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut builder = tar::Builder::new(Vec::new());
+    for (name, content) in files {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(content.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder.append_data(&mut header, name, *content).unwrap();
+    }
+    builder.into_inner().unwrap()
+}
+
+fn gzip(content: &[u8]) -> Vec<u8> {
+    let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(content).unwrap();
+    encoder.finish().unwrap()
+}
+
+fn digest(content: &[u8]) -> String {
+    let hex: String = sha256(content)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    format!("sha256:{}", hex)
+}
+
+fn blob_path(content: &[u8]) -> String {
+    digest(content)
+        .replace(':', "/")
+        .replacen("sha256", "blobs/sha256", 1)
+}
+
+fn config() -> Vec<u8> {
+    json!({
+        "architecture": "amd64",
+        "config": {
+            "Env": ["PATH=/usr/bin", format!("AWS_ACCESS_KEY_ID={}", AWS_API_KEY)],
+        },
+        "history": [
+            {"created_by": "/bin/sh -c #(nop) ADD file:1234 in /"},
+            {"empty_layer": true},
+            {"created_by": format!("/bin/sh -c aws configure set key {}", AWS_API_KEY)},
+        ],
+    })
+    .to_string()
+    .into_bytes()
+}
+
+// A key added by the first layer, then deleted by the second
+fn layers() -> [Vec<u8>; 2] {
+    let key = format!("[default]\naws_access_key_id = {}\n", AWS_API_KEY);
+    [
+        tar(&[
+            ("etc/aws/credentials", key.as_bytes()),
+            ("usr/bin/tool", b"\x7fELF\x00\x01"),
+        ]),
+        tar(&[("etc/aws/.wh.credentials", b""), ("etc/motd", b"hello\n")]),
+    ]
+}
+
+// Layer (or config) digest, path and line of each finding
+fn scan(path: &Path) -> Result<Vec<(String, String, usize)>, ImageErr> {
+    let mut source = ImageClient::from(path)?;
+    let detectors = Backend::default().detectors();
+
+    source
+        .scan(&detectors)
+        .map(|result| {
+            let finding = result?;
+            assert_eq!(finding.provenance.kind, SourceKind::Image);
+            Ok((
+                finding.provenance.layer.unwrap(),
+                finding.provenance.path.unwrap(),
+                finding.line,
+            ))
+        })
+        .collect()
+}
+
+// Every result, rather than stopping at the first error
+fn scan_all(path: &Path) -> Vec<Result<(String, String, usize), ImageErr>> {
+    let mut source = ImageClient::from(path).unwrap();
+    let detectors = Backend::default().detectors();
+
+    source
+        .scan(&detectors)
+        .map(|result| {
+            let finding = result?;
+            Ok((
+                finding.provenance.layer.unwrap(),
+                finding.provenance.path.unwrap(),
+                finding.line,
+            ))
+        })
+        .collect()
+}
+
+fn expected(config: &[u8], layer: &[u8]) -> Vec<(String, String, usize)> {
+    vec![
+        (digest(config), String::from(ENV_PATH), 2),
+        (digest(config), String::from(HISTORY_PATH), 3),
+        (digest(layer), String::from("etc/aws/credentials"), 2),
+    ]
+}
+
+#[test]
+fn tp_docker_save() {
+    let config = config();
+    let [first, second] = layers();
+    let manifest = json!([{
+        "Config": blob_path(&config),
+        "RepoTags": ["app:latest"],
+        "Layers": [blob_path(&first), blob_path(&second)],
+    }])
+    .to_string();
+
+    let tarball = tar(&[
+        ("manifest.json", manifest.as_bytes()),
+        (&blob_path(&config), &config),
+        (&blob_path(&first), &first),
+        (&blob_path(&second), &second),
+    ]);
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("image.tar");
+    fs::write(&path, tarball).unwrap();
+
+    assert_eq!(scan(&path).unwrap(), expected(&config, &first));
+}
+
+#[test]
+fn tp_oci_layout() {
+    let config = config();
+    let [first, second] = layers();
+    let (first, second) = (gzip(&first), gzip(&second));
+    let gzip_type = "application/vnd.oci.image.layer.v1.tar+gzip";
+    let manifest = json!({
+        "schemaVersion": 2,
+        "mediaType": "application/vnd.oci.image.manifest.v1+json",
+        "config": {"mediaType": "application/vnd.oci.image.config.v1+json", "digest": digest(&config)},
+        "layers": [
+            {"mediaType": gzip_type, "digest": digest(&first)},
+            {"mediaType": gzip_type, "digest": digest(&second)},
+        ],
+    })
+    .to_string()
+    .into_bytes();
+    // a multi-platform index, within the top-level index
+    let platforms = json!({
+        "schemaVersion": 2,
+        "manifests": [{"digest": digest(&manifest), "platform": {"os": "linux"}}],
+    })
+    .to_string()
+    .into_bytes();
+    let index = json!({"schemaVersion": 2, "manifests": [{"digest": digest(&platforms)}]});
+
+    let dir = TempDir::new().unwrap();
+    fs::create_dir_all(dir.path().join("blobs/sha256")).unwrap();
+    fs::write(
+        dir.path().join("oci-layout"),
+        r#"{"imageLayoutVersion":"1.0.0"}"#,
+    )
+    .unwrap();
+    fs::write(dir.path().join("index.json"), index.to_string()).unwrap();
+    for blob in [&config, &first, &second, &manifest, &platforms] {
+        fs::write(dir.path().join(blob_path(blob)), blob).unwrap();
+    }
+
+    assert_eq!(scan(dir.path()).unwrap(), expected(&config, &first));
+}
+
+#[test]
+fn fp_blob_missing() {
+    let config = config();
+    let manifest = json!([{"Config": blob_path(&config), "Layers": ["missing/layer.tar"]}]);
+    let tarball = tar(&[
+        ("manifest.json", manifest.to_string().as_bytes()),
+        (&blob_path(&config), &config),
+    ]);
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("image.tar");
+    fs::write(&path, tarball).unwrap();
+
+    assert_eq!(scan(&path), Err(ImageErr::BlobMissing));
+}
+
+#[test]
+fn fp_layer_unreadable() {
+    let [first, _] = layers();
+    let zstd = b"\x28\xb5\x2f\xfd\x00\x00".to_vec();
+    let corrupt = gzip(b"not a tar archive, but long enough to be read as a header");
+    let manifest = json!([{
+        "Config": "missing/config.json",
+        "Layers": [blob_path(&zstd), blob_path(&corrupt), blob_path(&first)],
+    }]);
+    let tarball = tar(&[
+        ("manifest.json", manifest.to_string().as_bytes()),
+        (&blob_path(&zstd), &zstd),
+        (&blob_path(&corrupt), &corrupt),
+        (&blob_path(&first), &first),
+    ]);
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("image.tar");
+    fs::write(&path, tarball).unwrap();
+
+    // the layers after those which cannot be read are still scanned
+    assert_eq!(
+        scan_all(&path),
+        vec![
+            Err(ImageErr::BlobMissing),
+            Err(ImageErr::LayerUnsupported),
+            Err(ImageErr::LayerCorrupt),
+            Ok((digest(&first), String::from("etc/aws/credentials"), 2)),
+        ]
+    );
+}
+
+#[test]
+fn fp_path_invalid() {
+    let config = config();
+    let [first, _] = layers();
+    // files next to the image, which its manifests must not lead to
+    let dir = TempDir::new().unwrap();
+    fs::write(dir.path().join("config.json"), &config).unwrap();
+    fs::write(dir.path().join("layer.tar"), &first).unwrap();
+    let outside = dir.path().join("layer.tar");
+
+    let image = dir.path().join("image");
+    fs::create_dir_all(image.join("blobs/sha256")).unwrap();
+    fs::write(image.join(blob_path(&first)), &first).unwrap();
+    let manifest = json!([{
+        "Config": "../config.json",
+        "Layers": [outside.to_str().unwrap(), "blobs/../../layer.tar", blob_path(&first)],
+    }]);
+    fs::write(image.join("manifest.json"), manifest.to_string()).unwrap();
+
+    assert_eq!(
+        scan_all(&image),
+        vec![
+            Err(ImageErr::PathInvalid),
+            Err(ImageErr::PathInvalid),
+            Err(ImageErr::PathInvalid),
+            Ok((digest(&first), String::from("etc/aws/credentials"), 2)),
+        ]
+    );
+
+    // a digest is only made a path if it is <algorithm>:<hex>
+    fs::remove_file(image.join("manifest.json")).unwrap();
+    for digest in [
+        "sha256:../../layer.tar",
+        "sha256:/etc/shadow",
+        "../layer.tar",
+    ] {
+        let index = json!({"schemaVersion": 2, "manifests": [{"digest": digest}]});
+        fs::write(image.join("index.json"), index.to_string()).unwrap();
+
+        assert_eq!(scan(&image), Err(ImageErr::PathInvalid), "{}", digest);
+    }
+}
+
+#[test]
+fn fp_not_an_image() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("files.tar");
+    fs::write(&path, tar(&[("README", b"hello\n")])).unwrap();
+
+    assert_eq!(scan(&path), Err(ImageErr::NotAnImage));
+    assert_eq!(scan(dir.path()), Err(ImageErr::NotAnImage));
+    assert!(matches!(
+        ImageClient::from(&dir.path().join("missing.tar")),
+        Err(ImageErr::NotFound)
+    ));
+}
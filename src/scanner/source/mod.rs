@@ -1 +1,11 @@
+pub mod archive;
+pub mod filesystem;
 pub mod git;
+pub mod image;
+pub mod stdin;
+pub mod traits;
+
+pub use traits::{Chunk, CommitInfo, Provenance, Source, SourceFinding, SourceKind};
+
+#[cfg(test)]
+pub mod test;
@@ -0,0 +1,172 @@
+use crate::scanner::finding::private_key::pem::{BOUNDARY_BEGIN_FOOTER, BOUNDARY_BEGIN_HEADER};
+use crate::scanner::source::traits::{Chunk, Provenance, Source, SourceKind};
+use std::io::{self, ErrorKind, Read, Stdin};
+
+// Scanning of standard input (or any other reader), e.g. at the end of a shell
+// pipeline, in bounded memory. Input is read in chunks, which are cut at line
+// ends so that line numbers carry on from one chunk to the next.
+//
+// A PEM block must be in a single chunk to be found, so when a chunk would end
+// within one (after its header, but before its footer), the block is carried
+// over to the next chunk instead. A block is only carried up to a maximum size,
+// after which it is scanned as it is.
+
+pub const CHUNK_SIZE: usize = 64 * 1024;
+pub const MAX_CARRY: usize = 1024 * 1024;
+
+#[derive(Clone, Debug)]
+pub struct StdinOptions {
+    pub chunk_size: usize, // bytes read at a time
+    pub max_carry: usize,  // of a PEM block (or line) carried over to the next chunk
+}
+
+impl Default for StdinOptions {
+    fn default() -> StdinOptions {
+        StdinOptions {
+            chunk_size: CHUNK_SIZE,
+            max_carry: MAX_CARRY,
+        }
+    }
+}
+
+pub struct StdinSource<R: Read = Stdin> {
+    reader: R,
+    options: StdinOptions,
+}
+
+impl StdinSource<Stdin> {
+    pub fn new() -> StdinSource<Stdin> {
+        StdinSource::from_reader(io::stdin())
+    }
+}
+
+impl Default for StdinSource<Stdin> {
+    fn default() -> StdinSource<Stdin> {
+        StdinSource::new()
+    }
+}
+
+impl<R: Read> StdinSource<R> {
+    pub fn from_reader(reader: R) -> StdinSource<R> {
+        StdinSource::with_options(reader, StdinOptions::default())
+    }
+
+    pub fn with_options(reader: R, options: StdinOptions) -> StdinSource<R> {
+        StdinSource { reader, options }
+    }
+}
+
+fn rfind(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).rposition(|w| w == needle)
+}
+
+// Start of the line with the header of the last PEM block, if it has no footer
+fn unfinished_block(input: &[u8]) -> Option<usize> {
+    let header = rfind(input, BOUNDARY_BEGIN_HEADER.as_bytes())?;
+    if rfind(&input[header..], BOUNDARY_BEGIN_FOOTER.as_bytes()).is_some() {
+        return None;
+    }
+
+    Some(rfind(&input[..header], b"\n").map_or(0, |i| i + 1))
+}
+
+pub struct StdinChunks<'a, R: Read> {
+    reader: &'a mut R,
+    options: &'a StdinOptions,
+    buffer: Vec<u8>,
+    lines: usize, // before the start of the buffer
+    eof: bool,
+}
+
+impl<R: Read> StdinChunks<'_, R> {
+    fn fill(&mut self) -> io::Result<()> {
+        let target = self.buffer.len() + self.options.chunk_size;
+        while !self.eof && self.buffer.len() < target {
+            let start = self.buffer.len();
+            self.buffer.resize(target, 0);
+            match self.reader.read(&mut self.buffer[start..]) {
+                Ok(0) => {
+                    self.buffer.truncate(start);
+                    self.eof = true;
+                }
+                Ok(count) => self.buffer.truncate(start + count),
+                Err(e) if e.kind() == ErrorKind::Interrupted => self.buffer.truncate(start),
+                Err(e) => {
+                    self.buffer.truncate(start);
+                    return Err(e);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // Where the next chunk should end within the buffer, if it should yet
+    fn chunk_end(&self) -> Option<usize> {
+        if self.eof {
+            return Some(self.buffer.len());
+        }
+
+        let carry_limit = self.buffer.len() >= self.options.max_carry;
+        let end = match rfind(&self.buffer, b"\n") {
+            Some(newline) => newline + 1,
+            // a line too long to carry over is cut where it is
+            None if carry_limit => return Some(self.buffer.len()),
+            None => return None,
+        };
+
+        match unfinished_block(&self.buffer[..end]) {
+            Some(0) if carry_limit => Some(end),
+            Some(0) => None,
+            Some(start) => Some(start),
+            None => Some(end),
+        }
+    }
+}
+
+impl<R: Read> Iterator for StdinChunks<'_, R> {
+    type Item = io::Result<Chunk>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Err(e) = self.fill() {
+                self.eof = true;
+                self.buffer.clear();
+                return Some(Err(e));
+            }
+            if self.eof && self.buffer.is_empty() {
+                return None;
+            }
+
+            if let Some(end) = self.chunk_end() {
+                let content: Vec<u8> = self.buffer.drain(..end).collect();
+                let mut provenance = Provenance::new(SourceKind::Stdin);
+                provenance.line_offset = self.lines;
+                self.lines += content.iter().filter(|b| **b == b'\n').count();
+
+                return Some(Ok(Chunk {
+                    content,
+                    provenance,
+                }));
+            }
+        }
+    }
+}
+
+impl<R: Read> Source for StdinSource<R> {
+    type Error = io::Error;
+
+    fn kind(&self) -> SourceKind {
+        SourceKind::Stdin
+    }
+
+    fn chunks<'a>(&'a mut self) -> Box<dyn Iterator<Item = Result<Chunk, io::Error>> + 'a> {
+        Box::new(StdinChunks {
+            reader: &mut self.reader,
+            options: &self.options,
+            buffer: Vec::new(),
+            lines: 0,
+            eof: false,
+        })
+    }
+}
@@ -0,0 +1,122 @@
+use crate::scanner::detector::scan;
+use crate::scanner::detector::DetectorSet;
+use crate::scanner::finding::Finding;
+use std::fmt::Debug;
+use std::ops::Range;
+
+// Common interface implemented by every data source, so that any source can be
+// scanned with any detectors. A source yields chunks of its content, each with
+// the provenance of where it came from; every finding in a chunk is reported
+// with its provenance.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SourceKind {
+    Git,
+    Filesystem,
+    Stdin,
+    Archive,
+    Image,
+}
+
+impl SourceKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            SourceKind::Git => "git",
+            SourceKind::Filesystem => "filesystem",
+            SourceKind::Stdin => "stdin",
+            SourceKind::Archive => "archive",
+            SourceKind::Image => "image",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CommitInfo {
+    pub id: String,
+    pub author: String,
+    pub email: String,
+    pub timestamp: i64, // seconds since the unix epoch
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Provenance {
+    pub kind: SourceKind,
+    pub path: Option<String>,
+    pub commit: Option<CommitInfo>,
+    pub layer: Option<String>, // digest of the container image layer (or config)
+    // lines of the file before the first line of the chunk, for a chunk which
+    // is only part of a file (e.g. the lines added by a commit)
+    pub line_offset: usize,
+}
+
+impl Provenance {
+    pub fn new(kind: SourceKind) -> Provenance {
+        Provenance {
+            kind,
+            path: None,
+            commit: None,
+            layer: None,
+            line_offset: 0,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Chunk {
+    pub content: Vec<u8>,
+    pub provenance: Provenance,
+}
+
+#[derive(Clone, Debug)]
+pub struct SourceFinding {
+    pub provenance: Provenance,
+    pub line: usize,   // in the file, i.e. including the line offset of the chunk
+    pub column: usize, // in bytes
+    pub column_utf16: usize, // in UTF-16 code units, as editors count it
+    pub span: Range<usize>, // bytes of the chunk's content the finding was found in
+    pub finding: Finding,
+}
+
+impl Chunk {
+    // Runs the detectors over the chunk
+    pub fn scan(&self, detectors: &DetectorSet) -> Vec<SourceFinding> {
+        detectors
+            .detect(&self.content)
+            .map(|detection| SourceFinding {
+                provenance: self.provenance.clone(),
+                line: self.provenance.line_offset + detection.line,
+                column: detection.column,
+                column_utf16: scan::utf16_column(
+                    &self.content,
+                    detection.span.start,
+                    detection.column,
+                ),
+                span: detection.span,
+                finding: detection.finding,
+            })
+            .collect()
+    }
+}
+
+pub trait Source {
+    type Error: Debug;
+
+    fn kind(&self) -> SourceKind;
+
+    // Every chunk of the source, in the order the source is read
+    fn chunks<'a>(&'a mut self) -> Box<dyn Iterator<Item = Result<Chunk, Self::Error>> + 'a>;
+
+    // Runs the detectors over every chunk of the source
+    fn scan<'a>(
+        &'a mut self,
+        detectors: &'a DetectorSet,
+    ) -> Box<dyn Iterator<Item = Result<SourceFinding, Self::Error>> + 'a>
+    where
+        Self::Error: 'a,
+    {
+        Box::new(self.chunks().flat_map(move |chunk| match chunk {
+            Ok(chunk) => chunk.scan(detectors).into_iter().map(Ok).collect(),
+            Err(e) => vec![Err(e)],
+        }))
+    }
+}